@@ -1,6 +1,10 @@
-use std::io::Read;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 use crate::error::ShnError;
+use crate::io::Read;
 
 /// MSB-first bit reader over any `Read` source.
 ///
@@ -135,12 +139,199 @@ impl<R: Read> BitReader<R> {
         &self.reader
     }
 
+    /// Get a mutable reference to the underlying reader.
+    pub fn inner_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
     /// Consume the BitReader and return the underlying reader.
     pub fn into_inner(self) -> R {
         self.reader
     }
 }
 
+/// A point a push-mode `BitReader` can later rewind to — the in-memory-ring
+/// counterpart of `BitCheckpoint`. Captured right before a decode attempt
+/// that might run out of buffered input partway through, so it can be
+/// retried bit-for-bit exactly once `crate::push::PushDecoder::feed` supplies
+/// more data. Unlike `BitCheckpoint`, this only needs `crate::io::Mark`, not
+/// a full `std::io::Seek`, so it works in `no_std` builds too.
+#[derive(Debug, Clone, Copy)]
+pub struct BitMark {
+    reader_pos: usize,
+    buf: u32,
+    bits_left: u32,
+}
+
+impl<R: Read + crate::io::Mark> BitReader<R> {
+    /// Capture the current bit position so decoding can later resume here
+    /// via [`BitReader::rewind`].
+    pub fn mark(&self) -> BitMark {
+        BitMark {
+            reader_pos: self.reader.mark(),
+            buf: self.buf,
+            bits_left: self.bits_left,
+        }
+    }
+
+    /// Rewind to a position captured by [`BitReader::mark`].
+    pub fn rewind(&mut self, m: &BitMark) {
+        self.reader.rewind(m.reader_pos);
+        self.buf = m.buf;
+        self.bits_left = m.bits_left;
+    }
+
+    /// Tell the underlying reader this position will never be rewound past
+    /// again, so it can reclaim any buffered bytes before it.
+    pub fn commit(&mut self) {
+        self.reader.commit();
+    }
+}
+
+/// MSB-first bit writer over any `Write` sink — the encoder counterpart to
+/// [`BitReader`].
+///
+/// Mirrors `BitReader`'s packing exactly: bits accumulate left-justified in
+/// a 32-bit buffer and are flushed a byte at a time once 8 or more are
+/// buffered, so a `BitWriter` followed by a `BitReader` round-trips
+/// bit-for-bit.
+#[cfg(feature = "std")]
+pub struct BitWriter<W: std::io::Write> {
+    writer: W,
+    /// Bit accumulator — bits are left-justified (MSB = next bit to flush).
+    buf: u32,
+    /// Number of valid (not yet flushed) bits in `buf`.
+    bits_used: u32,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> BitWriter<W> {
+    pub fn new(writer: W) -> Self {
+        BitWriter {
+            writer,
+            buf: 0,
+            bits_used: 0,
+        }
+    }
+
+    /// Write a single byte directly to the underlying stream (bypasses the
+    /// bit buffer). Used for the magic and version bytes before bitstream
+    /// mode begins.
+    pub fn write_byte_direct(&mut self, b: u8) -> Result<(), ShnError> {
+        self.writer.write_all(&[b])?;
+        Ok(())
+    }
+
+    /// Write the low `n` bits of `val` (MSB-first). Max 25 bits per call,
+    /// matching `BitReader::read_bits`.
+    pub fn write_bits(&mut self, n: u32, val: u32) -> Result<(), ShnError> {
+        debug_assert!(n <= 25, "write_bits limited to 25 bits per call");
+        if n == 0 {
+            return Ok(());
+        }
+        let masked = if n >= 32 { val } else { val & ((1u32 << n) - 1) };
+        self.buf |= masked << (32 - self.bits_used - n);
+        self.bits_used += n;
+
+        while self.bits_used >= 8 {
+            let byte = (self.buf >> 24) as u8;
+            self.writer.write_all(&[byte])?;
+            self.buf <<= 8;
+            self.bits_used -= 8;
+        }
+        Ok(())
+    }
+
+    /// Write an unsigned Rice-coded value with parameter `k`: `q` zero bits
+    /// (the quotient `value >> k`), a stop bit, then `k` mantissa bits.
+    pub fn write_unsigned_rice(&mut self, k: u32, value: u32) -> Result<(), ShnError> {
+        let q = value >> k.min(31);
+        for _ in 0..q {
+            self.write_bits(1, 0)?;
+        }
+        self.write_bits(1, 1)?;
+        if k > 0 {
+            self.write_bits(k, value)?;
+        }
+        Ok(())
+    }
+
+    /// Write a signed Rice-coded value: sign-fold (`0->0, -1->1, 1->2, ...`)
+    /// then unsigned-Rice-code with `k+1` mantissa bits.
+    pub fn write_signed_rice(&mut self, k: u32, value: i32) -> Result<(), ShnError> {
+        let folded = if value >= 0 {
+            (value as u32) << 1
+        } else {
+            (((-(value + 1)) as u32) << 1) | 1
+        };
+        self.write_unsigned_rice(k + 1, folded)
+    }
+
+    /// Write a "ulong" — Shorten's variable-length unsigned integer: the
+    /// bit-length of `value` Rice-coded with `ULONGSIZE=2`, then `value`
+    /// itself Rice-coded with that many mantissa bits.
+    pub fn write_ulong(&mut self, value: u32) -> Result<(), ShnError> {
+        let nbits = 32 - value.leading_zeros();
+        self.write_unsigned_rice(2, nbits)?;
+        self.write_unsigned_rice(nbits, value)
+    }
+
+    /// Pad out any partial trailing byte with zero bits and flush to the
+    /// underlying writer.
+    pub fn flush(&mut self) -> Result<(), ShnError> {
+        if self.bits_used > 0 {
+            let byte = (self.buf >> 24) as u8;
+            self.writer.write_all(&[byte])?;
+            self.buf = 0;
+            self.bits_used = 0;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Flush and consume the writer, returning the underlying sink.
+    pub fn into_inner(mut self) -> Result<W, ShnError> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// A snapshot of a `BitReader`'s position, sufficient to resume decoding
+/// bit-exactly without re-reading any already-consumed bytes.
+///
+/// Captures the underlying byte offset *and* the leftover bit accumulator,
+/// since Shorten commands are not byte-aligned — restoring the byte offset
+/// alone would lose up to 31 bits of buffered state.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct BitCheckpoint {
+    byte_pos: u64,
+    buf: u32,
+    bits_left: u32,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + std::io::Seek> BitReader<R> {
+    /// Capture the current bit position so decoding can later resume here
+    /// via [`BitReader::restore`].
+    pub fn checkpoint(&mut self) -> Result<BitCheckpoint, ShnError> {
+        let byte_pos = self.reader.stream_position()?;
+        Ok(BitCheckpoint {
+            byte_pos,
+            buf: self.buf,
+            bits_left: self.bits_left,
+        })
+    }
+
+    /// Restore a position captured by [`BitReader::checkpoint`].
+    pub fn restore(&mut self, cp: &BitCheckpoint) -> Result<(), ShnError> {
+        self.reader.seek(std::io::SeekFrom::Start(cp.byte_pos))?;
+        self.buf = cp.buf;
+        self.bits_left = cp.bits_left;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +417,27 @@ mod tests {
         assert_eq!(br.read_ulong().unwrap(), 0);
     }
 
+    #[test]
+    fn bit_writer_matches_reader_round_trip() {
+        let mut buf: Vec<u8> = Vec::new();
+        {
+            let mut bw = BitWriter::new(&mut buf);
+            bw.write_bits(4, 0b1010).unwrap();
+            bw.write_bits(4, 0b0101).unwrap();
+            bw.write_unsigned_rice(2, 5).unwrap();
+            bw.write_signed_rice(0, -1).unwrap();
+            bw.write_ulong(5).unwrap();
+            bw.flush().unwrap();
+        }
+
+        let mut br = BitReader::new(buf.as_slice());
+        assert_eq!(br.read_bits(4).unwrap(), 0b1010);
+        assert_eq!(br.read_bits(4).unwrap(), 0b0101);
+        assert_eq!(br.read_unsigned_rice(2).unwrap(), 5);
+        assert_eq!(br.read_signed_rice(0).unwrap(), -1);
+        assert_eq!(br.read_ulong().unwrap(), 5);
+    }
+
     #[test]
     fn decode_real_header() {
         // Real SHN header bytes (after magic+version) for type=5, ch=2, bs=256:
@@ -1,3 +1,8 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
 /// Number of history samples maintained before the current block.
 ///
 /// The highest-order fixed predictor (DIFF3) uses coefficients [3, -3, 1],
@@ -9,6 +14,7 @@ pub const NWRAP: usize = 3;
 /// The buffer stores `NWRAP` history samples followed by the current block.
 /// Indexing is relative to the start of the current block, so index -1
 /// refers to the last sample of the previous block (history region).
+#[derive(Clone)]
 pub struct ChannelBuffer {
     /// Sample storage: [history (NWRAP)] [current block (blocksize)]
     data: Vec<i32>,
@@ -68,6 +74,7 @@ impl ChannelBuffer {
 ///
 /// Shorten uses a running mean of recent sample blocks to center the residuals.
 /// The mean window size is `nmean` (typically 4 for v2+).
+#[derive(Clone)]
 pub struct MeanAccumulator {
     /// Circular buffer of block means.
     values: Vec<i32>,
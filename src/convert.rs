@@ -0,0 +1,277 @@
+//! Sample-format conversion and channel remixing for decoded PCM.
+//!
+//! Modeled loosely on nihav's `soundcvt` module: consumers often want
+//! per-channel planar buffers, a different sample format than the decoder's
+//! native `i32`, or a remixed channel count, rather than raw interleaved
+//! samples straight off [`crate::ShnReader`].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::header::SampleType;
+
+/// A named channel-remixing operation, for the common cases that don't
+/// warrant hand-writing a [`RemixMatrix`].
+pub enum RemixOp {
+    /// Copy every channel through unchanged.
+    Passthrough,
+    /// Reassemble output channels from source channel indices, e.g.
+    /// `Reorder(vec![1, 0])` swaps a stereo pair.
+    Reorder(Vec<usize>),
+    /// Duplicate a single source channel to both stereo outputs.
+    MonoToStereo,
+    /// `(L + R) / 2` downmix.
+    StereoToMono,
+    /// An explicit weighted matrix, for anything the named ops don't cover.
+    Matrix(RemixMatrix),
+}
+
+impl RemixOp {
+    /// Resolve this op into the [`RemixMatrix`] `remix` actually applies.
+    /// `src_channels` is only consulted by `Passthrough` and `Reorder`,
+    /// which need to know how many source channels each row spans.
+    pub fn into_matrix(self, src_channels: usize) -> RemixMatrix {
+        match self {
+            RemixOp::Passthrough => RemixMatrix::new(
+                (0..src_channels)
+                    .map(|dst| (0..src_channels).map(|src| (src == dst) as u8 as f32).collect())
+                    .collect(),
+            ),
+            RemixOp::Reorder(order) => RemixMatrix::new(
+                order
+                    .iter()
+                    .map(|&src| (0..src_channels).map(|i| (i == src) as u8 as f32).collect())
+                    .collect(),
+            ),
+            RemixOp::MonoToStereo => RemixMatrix::mono_to_stereo(),
+            RemixOp::StereoToMono => RemixMatrix::stereo_to_mono(),
+            RemixOp::Matrix(m) => m,
+        }
+    }
+}
+
+/// A remix matrix mapping source channels to destination channels.
+///
+/// `weights[dst][src]` is the gain applied to source channel `src` when
+/// computing destination channel `dst`.
+pub struct RemixMatrix {
+    weights: Vec<Vec<f32>>,
+}
+
+impl RemixMatrix {
+    /// Build a remix matrix from an explicit NxM weight table: one row per
+    /// destination channel, one weight per source channel.
+    pub fn new(weights: Vec<Vec<f32>>) -> Self {
+        RemixMatrix { weights }
+    }
+
+    /// The common stereo -> mono downmix: `(L + R) / 2`.
+    pub fn stereo_to_mono() -> Self {
+        RemixMatrix::new(vec![vec![0.5, 0.5]])
+    }
+
+    /// Duplicate a single source channel to both stereo outputs.
+    pub fn mono_to_stereo() -> Self {
+        RemixMatrix::new(vec![vec![1.0], vec![1.0]])
+    }
+
+    /// Number of channels this matrix produces.
+    pub fn dst_channels(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn apply_frame(&self, frame: &[i32], out: &mut Vec<i32>) {
+        for row in &self.weights {
+            let mut acc = 0f32;
+            for (src, &w) in row.iter().enumerate() {
+                acc += frame.get(src).copied().unwrap_or(0) as f32 * w;
+            }
+            out.push(round_half_away_from_zero(acc) as i32);
+        }
+    }
+}
+
+/// Round-half-away-from-zero for `f32`, without relying on `f32::round`
+/// (a libstd-only method — this module has no `#[cfg(feature = "std")]`
+/// gate, so it must build under plain `core` too).
+fn round_half_away_from_zero(x: f32) -> f32 {
+    let truncated = x as i64 as f32;
+    let frac = x - truncated;
+    if frac >= 0.5 {
+        truncated + 1.0
+    } else if frac <= -0.5 {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+/// Deinterleave `interleaved` (frame-major, `channels` samples per frame)
+/// into one `Vec<i32>` per channel.
+pub fn planar(interleaved: &[i32], channels: usize) -> Vec<Vec<i32>> {
+    let channels = channels.max(1);
+    let frames = interleaved.len() / channels;
+    let mut planes: Vec<Vec<i32>> = (0..channels).map(|_| Vec::with_capacity(frames)).collect();
+    for frame in interleaved.chunks_exact(channels) {
+        for (ch, &s) in frame.iter().enumerate() {
+            planes[ch].push(s);
+        }
+    }
+    planes
+}
+
+/// Remix an interleaved stream through `matrix`, returning a new
+/// interleaved stream with `matrix.dst_channels()` channels per frame.
+pub fn remix(interleaved: &[i32], src_channels: usize, matrix: &RemixMatrix) -> Vec<i32> {
+    let src_channels = src_channels.max(1);
+    let mut out = Vec::with_capacity(interleaved.len() / src_channels * matrix.dst_channels());
+    for frame in interleaved.chunks(src_channels) {
+        matrix.apply_frame(frame, &mut out);
+    }
+    out
+}
+
+/// Remove the unsigned-type DC bias so every sample type can be treated as
+/// signed before further scaling: `TYPE_U8`/`TYPE_U16*` files store samples
+/// offset by the format's midpoint (e.g. U8 is `0..255` instead of
+/// `-128..127`) rather than two's-complement. A no-op for signed types.
+pub fn debias(sample: i32, sample_type: SampleType) -> i32 {
+    if sample_type.is_unsigned() {
+        sample - (1i32 << (sample_type.bits() - 1))
+    } else {
+        sample
+    }
+}
+
+/// Scale a sample from its native bit depth to full-range `i8`, rounding
+/// toward negative infinity (arithmetic shift) on down-conversion.
+pub fn to_i8(sample: i32, bits_per_sample: u32) -> i8 {
+    let shift = 8i32 - bits_per_sample as i32;
+    if shift >= 0 {
+        (sample << shift) as i8
+    } else {
+        (sample >> -shift) as i8
+    }
+}
+
+/// Scale a sample from its native bit depth to full-range `i16`, rounding
+/// toward negative infinity (arithmetic shift) on down-conversion.
+pub fn to_i16(sample: i32, bits_per_sample: u32) -> i16 {
+    let shift = 16i32 - bits_per_sample as i32;
+    if shift >= 0 {
+        (sample << shift) as i16
+    } else {
+        (sample >> -shift) as i16
+    }
+}
+
+/// Scale a sample from its native bit depth to full-range 24-bit, stored in
+/// the low 24 bits of an `i32` and clamped to the 24-bit range (there is no
+/// native `i24` type).
+pub fn to_i24(sample: i32, bits_per_sample: u32) -> i32 {
+    let shift = 24i32 - bits_per_sample as i32;
+    let scaled = if shift >= 0 { sample << shift } else { sample >> -shift };
+    scaled.clamp(-(1 << 23), (1 << 23) - 1)
+}
+
+/// Scale a sample from its native bit depth to full-range `i32`.
+pub fn to_i32(sample: i32, bits_per_sample: u32) -> i32 {
+    let shift = 32i32 - bits_per_sample as i32;
+    if shift >= 0 {
+        sample << shift
+    } else {
+        sample >> -shift
+    }
+}
+
+/// Scale a sample from its native bit depth to normalized `f32` in `[-1, 1]`.
+pub fn to_f32(sample: i32, bits_per_sample: u32) -> f32 {
+    let full_scale = (1i64 << (bits_per_sample - 1).min(62)) as f32;
+    sample as f32 / full_scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn planar_deinterleaves() {
+        let interleaved = [1, 10, 2, 20, 3, 30];
+        let planes = planar(&interleaved, 2);
+        assert_eq!(planes[0], vec![1, 2, 3]);
+        assert_eq!(planes[1], vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn round_half_away_from_zero_matches_f32_round() {
+        for x in [0.0f32, 0.4, 0.5, 0.6, 1.5, 2.5, -0.4, -0.5, -0.6, -1.5, -2.5] {
+            assert_eq!(round_half_away_from_zero(x), x.round());
+        }
+    }
+
+    #[test]
+    fn remix_stereo_to_mono() {
+        let interleaved = [0, 100, 10, 90];
+        let out = remix(&interleaved, 2, &RemixMatrix::stereo_to_mono());
+        assert_eq!(out, vec![50, 50]);
+    }
+
+    #[test]
+    fn to_i16_scales_up_from_8_bit() {
+        assert_eq!(to_i16(1, 8), 256);
+    }
+
+    #[test]
+    fn to_f32_full_scale() {
+        assert!((to_f32(i16::MAX as i32, 16) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn to_i8_scales_down_from_16_bit() {
+        assert_eq!(to_i8(256, 16), 1);
+    }
+
+    #[test]
+    fn to_i24_clamps_to_range() {
+        assert_eq!(to_i24(i32::MAX, 32), (1 << 23) - 1);
+    }
+
+    #[test]
+    fn to_i32_scales_up_from_16_bit() {
+        assert_eq!(to_i32(1, 16), 1 << 16);
+    }
+
+    #[test]
+    fn debias_centers_unsigned_8_bit() {
+        assert_eq!(debias(128, SampleType::Unsigned8), 0);
+        assert_eq!(debias(0, SampleType::Unsigned8), -128);
+    }
+
+    #[test]
+    fn debias_is_noop_for_signed_types() {
+        assert_eq!(debias(-5, SampleType::Signed16LittleEndian), -5);
+    }
+
+    #[test]
+    fn reorder_swaps_stereo_channels() {
+        let interleaved = [1, 2, 3, 4];
+        let matrix = RemixOp::Reorder(vec![1, 0]).into_matrix(2);
+        assert_eq!(remix(&interleaved, 2, &matrix), vec![2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn mono_to_stereo_duplicates_the_channel() {
+        let interleaved = [7, 9];
+        let matrix = RemixOp::MonoToStereo.into_matrix(1);
+        assert_eq!(remix(&interleaved, 1, &matrix), vec![7, 7, 9, 9]);
+    }
+
+    #[test]
+    fn passthrough_is_identity() {
+        let interleaved = [1, 2, 3, 4];
+        let matrix = RemixOp::Passthrough.into_matrix(2);
+        assert_eq!(remix(&interleaved, 2, &matrix), interleaved.to_vec());
+    }
+}
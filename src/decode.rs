@@ -1,28 +1,34 @@
-use std::io::Read;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 use crate::bitstream::BitReader;
 use crate::buffer::{ChannelBuffer, MeanAccumulator};
 use crate::error::ShnError;
-use crate::header::ShnHeader;
+use crate::header::{SampleType, ShnHeader};
+use crate::io::Read;
 
 // ─── Command IDs ─────────────────────────────────────────────────────────────
-const FN_DIFF0: i32 = 0;
-const FN_DIFF1: i32 = 1;
-const FN_DIFF2: i32 = 2;
-const FN_DIFF3: i32 = 3;
-const FN_QUIT: i32 = 4;
-const FN_BLOCKSIZE: i32 = 5;
+// `pub(crate)` so the encoder (`encode.rs`) can emit the same command IDs
+// the decoder reads.
+pub(crate) const FN_DIFF0: i32 = 0;
+pub(crate) const FN_DIFF1: i32 = 1;
+pub(crate) const FN_DIFF2: i32 = 2;
+pub(crate) const FN_DIFF3: i32 = 3;
+pub(crate) const FN_QUIT: i32 = 4;
+pub(crate) const FN_BLOCKSIZE: i32 = 5;
 const FN_BITSHIFT: i32 = 6;
-const FN_QLPC: i32 = 7;
+pub(crate) const FN_QLPC: i32 = 7;
 const FN_ZERO: i32 = 8;
 const FN_VERBATIM: i32 = 9;
 
 // ─── Constants ───────────────────────────────────────────────────────────────
-const FNSIZE: u32 = 2;
-const ENERGYSIZE: u32 = 3;
+pub(crate) const FNSIZE: u32 = 2;
+pub(crate) const ENERGYSIZE: u32 = 3;
 const BITSHIFTSIZE: u32 = 2;
-const LPCQSIZE: u32 = 2;
-const LPCQUANT: i32 = 5;
+pub(crate) const LPCQSIZE: u32 = 2;
+pub(crate) const LPCQUANT: i32 = 5;
 const VERBATIM_CKSIZE_SIZE: u32 = 5;
 const VERBATIM_BYTE_SIZE: u32 = 8;
 
@@ -31,7 +37,7 @@ const VERBATIM_BYTE_SIZE: u32 = 8;
 /// From TR-156: DIFF0 predicts 0 (no prediction), DIFF1 predicts sample[-1],
 /// DIFF2 predicts 2*sample[-1] - sample[-2], DIFF3 predicts
 /// 3*sample[-1] - 3*sample[-2] + sample[-3].
-const FIXED_COEFFS: [[i32; 3]; 4] = [
+pub(crate) const FIXED_COEFFS: [[i32; 3]; 4] = [
     [0, 0, 0],   // DIFF0: prediction = 0
     [1, 0, 0],   // DIFF1: prediction = s[-1]
     [2, -1, 0],  // DIFF2: prediction = 2*s[-1] - s[-2]
@@ -47,11 +53,25 @@ pub struct Decoder<R: Read> {
     pub nmean: usize,
     pub version: u8,
     pub bitshift: u32,
+    pub sample_type: SampleType,
 
     /// Per-channel sample buffers.
     pub buffers: Vec<ChannelBuffer>,
     /// Per-channel DC offset accumulators.
     pub means: Vec<MeanAccumulator>,
+    /// Per-channel scratch space for the raw 8-bit mu-law/A-law codes of
+    /// the block currently being decoded, captured in `finish_channel_block`
+    /// before they're expanded to linear samples. Empty for every other
+    /// sample type.
+    code_buffers: Vec<Vec<u8>>,
+    /// The current block's raw companded codes, interleaved the same way
+    /// as `output_buf` — the byte-exact counterpart to `output_buf` for
+    /// `SampleType::MuLaw`/`SampleType::ALaw` streams, since
+    /// `output_buf` only ever holds the expanded linear value. Used by
+    /// `ShnReader::decode_to_container` to reconstruct the original file
+    /// without the lossy round-trip a forward re-companding pass would
+    /// risk. Empty for every other sample type.
+    pub output_codes: Vec<u8>,
 
     /// Which channel we're currently decoding.
     pub current_channel: u32,
@@ -63,6 +83,55 @@ pub struct Decoder<R: Read> {
     pub output_pos: usize,
     /// The first audio command that was pre-read by the header parser.
     pending_cmd: Option<i32>,
+
+    /// Whether to refine the Rice parameter `k` sample-by-sample instead of
+    /// holding it fixed for the whole block (see `set_adaptive_rice`).
+    adaptive_rice: bool,
+    /// Per-channel running magnitude accumulator for adaptive Rice tracking.
+    /// Reset to `0` (meaning "uninitialized") whenever adaptive mode is
+    /// (re-)enabled; seeded from the block's declared `k` on first use.
+    rice_sums: Vec<i32>,
+
+    /// Raw bytes of the VERBATIM block(s) read before the first audio
+    /// command (typically the RIFF/WAVE or FORM/AIFF header), copied from
+    /// `ShnHeader::verbatim_header` at construction.
+    verbatim_header: Vec<u8>,
+    /// Raw bytes of every VERBATIM block encountered *after* audio
+    /// decoding has started — in practice, any trailing container bytes
+    /// (e.g. a WAVE file's padding or trailing chunks) that followed the
+    /// last audio block.
+    verbatim_trailer: Vec<u8>,
+
+    /// Total frames (per channel) decoded so far, used to resolve
+    /// `seek_to_sample`'s frame index against `snapshots`.
+    pub total_frames: u64,
+    /// State snapshots taken at each block-group boundary, enabling
+    /// `ShnReader::seek_to_sample`. Only populated when the underlying
+    /// reader supports `Seek` (see `snapshot_now`/`restore_snapshot`).
+    #[cfg(feature = "std")]
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// A decoder state snapshot captured at a block-group boundary (i.e. when
+/// `current_channel == 0`, before the next block for every channel is
+/// decoded).
+///
+/// Shorten blocks are not independent — each depends on the previous
+/// `NWRAP` samples per channel and on the running mean history — so
+/// restoring a snapshot means restoring all of that, not just the byte
+/// offset.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct Snapshot {
+    /// Index of the first frame decoded by the block-group that starts here.
+    pub frame_start: u64,
+    checkpoint: crate::bitstream::BitCheckpoint,
+    buffers: Vec<ChannelBuffer>,
+    means: Vec<MeanAccumulator>,
+    bitshift: u32,
+    blocksize: usize,
+    pending_cmd: Option<i32>,
+    verbatim_trailer_len: usize,
 }
 
 impl<R: Read> Decoder<R> {
@@ -83,13 +152,23 @@ impl<R: Read> Decoder<R> {
             nmean: header.nmean,
             version: header.version,
             bitshift: 0,
+            sample_type: header.sample_type,
             buffers,
             means,
+            code_buffers: vec![Vec::new(); nchan],
+            output_codes: Vec::new(),
             current_channel: 0,
             finished: false,
             output_buf: Vec::new(),
             output_pos: 0,
             pending_cmd: header.first_audio_cmd,
+            adaptive_rice: false,
+            rice_sums: vec![0; nchan],
+            verbatim_header: header.verbatim_header.clone(),
+            verbatim_trailer: Vec::new(),
+            total_frames: 0,
+            #[cfg(feature = "std")]
+            snapshots: Vec::new(),
         }
     }
 
@@ -140,18 +219,24 @@ impl<R: Read> Decoder<R> {
 
                 FN_VERBATIM => {
                     let nbytes = self.reader.read_unsigned_rice(VERBATIM_CKSIZE_SIZE)? as usize;
+                    self.verbatim_trailer.reserve(nbytes);
                     for _ in 0..nbytes {
-                        self.reader.read_unsigned_rice(VERBATIM_BYTE_SIZE)?;
+                        self.verbatim_trailer
+                            .push(self.reader.read_unsigned_rice(VERBATIM_BYTE_SIZE)? as u8);
                     }
                 }
 
                 FN_ZERO => {
                     let ch = self.current_channel as usize;
                     let bs = self.blocksize;
+                    // For companded types, silence isn't residual-domain 0 —
+                    // it's whichever 8-bit code `finish_channel_block`'s
+                    // expansion decodes to (near) zero.
+                    let zero = companding::residual_zero(self.sample_type);
                     let buf = &mut self.buffers[ch];
                     buf.resize(bs);
                     for i in 0..bs {
-                        buf.set(i as isize, 0);
+                        buf.set(i as isize, zero);
                     }
                     self.finish_channel_block(ch)?;
                     blocks_decoded += 1;
@@ -174,6 +259,7 @@ impl<R: Read> Decoder<R> {
 
         // Interleave the decoded blocks from all channels
         self.interleave_output();
+        self.total_frames += self.blocksize as u64;
         Ok(true)
     }
 
@@ -189,8 +275,16 @@ impl<R: Read> Decoder<R> {
         let coffset = self.means[ch].coffset(bs);
         let coeffs = &FIXED_COEFFS[order];
 
+        let mut k = energy;
+        if self.adaptive_rice && self.rice_sums[ch] == 0 {
+            self.rice_sums[ch] = 1i32 << (k + 4).min(30);
+        }
+
         for i in 0..bs {
-            let residual = self.reader.read_signed_rice(energy)?;
+            let residual = self.reader.read_signed_rice(k)?;
+            if self.adaptive_rice {
+                k = adapt_rice_k(&mut self.rice_sums[ch], k, residual);
+            }
             let ii = i as isize;
             let prediction = if order == 0 {
                 coffset
@@ -228,8 +322,16 @@ impl<R: Read> Decoder<R> {
         let buf = &mut self.buffers[ch];
         buf.resize(bs);
 
+        let mut k = energy;
+        if self.adaptive_rice && self.rice_sums[ch] == 0 {
+            self.rice_sums[ch] = 1i32 << (k + 4).min(30);
+        }
+
         for i in 0..bs {
-            let residual = self.reader.read_signed_rice(energy)?;
+            let residual = self.reader.read_signed_rice(k)?;
+            if self.adaptive_rice {
+                k = adapt_rice_k(&mut self.rice_sums[ch], k, residual);
+            }
             let ii = i as isize;
 
             let mut prediction: i64 = 0;
@@ -244,7 +346,8 @@ impl<R: Read> Decoder<R> {
         Ok(())
     }
 
-    /// Post-process a decoded channel block: apply bitshift, update mean, wrap around.
+    /// Post-process a decoded channel block: apply bitshift, expand
+    /// companded samples, update mean, wrap around.
     fn finish_channel_block(&mut self, ch: usize) -> Result<(), ShnError> {
         let bs = self.blocksize;
         let buf = &mut self.buffers[ch];
@@ -257,6 +360,28 @@ impl<R: Read> Decoder<R> {
             }
         }
 
+        // mu-law/A-law files store the companded 8-bit code in the residual
+        // domain, not linear PCM — stash the raw code (for byte-exact
+        // container reconstruction, see `output_codes`) before expanding it
+        // to a linear sample for `output_buf` and the running mean.
+        match self.sample_type {
+            SampleType::MuLaw | SampleType::ALaw => {
+                let codes = &mut self.code_buffers[ch];
+                codes.resize(bs, 0);
+                for i in 0..bs {
+                    let code = buf.get(i as isize) as u8;
+                    codes[i] = code;
+                    let linear = if self.sample_type == SampleType::MuLaw {
+                        companding::mulaw_decode(code)
+                    } else {
+                        companding::alaw_decode(code)
+                    };
+                    buf.set(i as isize, linear);
+                }
+            }
+            _ => {}
+        }
+
         // Update the running mean with this block's mean (sum / blocksize, rounded)
         if self.nmean > 0 {
             let block_sum: i64 = (0..bs).map(|i| buf.get(i as isize) as i64).sum();
@@ -302,6 +427,16 @@ impl<R: Read> Decoder<R> {
                 }
             }
         }
+
+        self.output_codes.clear();
+        if matches!(self.sample_type, SampleType::MuLaw | SampleType::ALaw) {
+            self.output_codes.reserve(nchan * bs);
+            for i in 0..bs {
+                for ch in 0..nchan {
+                    self.output_codes.push(self.code_buffers[ch][i]);
+                }
+            }
+        }
     }
 
     /// Get the next sample from the output buffer, or None if exhausted.
@@ -314,4 +449,385 @@ impl<R: Read> Decoder<R> {
             None
         }
     }
+
+    /// Enable or disable adaptive per-sample Rice parameter refinement
+    /// (Monkey's-Audio-style magnitude tracking), for files encoded with
+    /// adaptive coding rather than a single `k` per block.
+    ///
+    /// Must be called before decoding begins — it resets the per-channel
+    /// tracking state, which would otherwise desync mid-stream.
+    pub fn set_adaptive_rice(&mut self, enabled: bool) {
+        self.adaptive_rice = enabled;
+        for sum in &mut self.rice_sums {
+            *sum = 0;
+        }
+    }
+
+    /// Raw bytes of the VERBATIM block(s) read before the first audio
+    /// command — typically the original RIFF/WAVE or FORM/AIFF header, for
+    /// splicing decoded PCM back into an identical container.
+    pub fn verbatim_header(&self) -> &[u8] {
+        &self.verbatim_header
+    }
+
+    /// Raw bytes of any VERBATIM block(s) encountered after audio decoding
+    /// started — in practice, the container bytes that followed the last
+    /// audio block. Only complete once the stream has finished decoding.
+    pub fn verbatim_trailer(&self) -> &[u8] {
+        &self.verbatim_trailer
+    }
+}
+
+/// Update the adaptive Rice tracking accumulator after decoding one
+/// residual `v` with parameter `k`, returning the parameter to use for the
+/// *next* residual.
+///
+/// `sum` tracks a running estimate of residual magnitude; `k` is nudged
+/// down when the tracked magnitude falls below `2^(k+4)` and up when it
+/// reaches `2^(k+5)`, keeping `2^k` roughly matched to the true magnitude
+/// as it drifts across a block.
+fn adapt_rice_k(sum: &mut i32, k: u32, v: i32) -> u32 {
+    *sum -= (*sum + 16) >> 5;
+    *sum += (v.unsigned_abs() as i32 + 1) / 2;
+
+    if *sum < (1i32 << (k + 4).min(30)) && k > 0 {
+        k - 1
+    } else if *sum >= (1i32 << (k + 5).min(30)) && k < 27 {
+        k + 1
+    } else {
+        k
+    }
+}
+
+/// Seeking support. Requires `Seek` because restoring a snapshot means
+/// rewinding the underlying byte stream to a position already consumed —
+/// something an in-memory buffer or a `File` can do, but an arbitrary
+/// `Read` (e.g. a network socket) cannot.
+#[cfg(feature = "std")]
+impl<R: Read + std::io::Seek> Decoder<R> {
+    /// Like `decode_next_block`, but first snapshots the state needed to
+    /// resume decoding right before this call, and keeps the snapshot (and
+    /// advances `total_frames`) only if a block was actually decoded.
+    fn decode_next_block_tracked(&mut self) -> Result<bool, ShnError> {
+        let snapshot = Snapshot {
+            frame_start: self.total_frames,
+            checkpoint: self.reader.checkpoint()?,
+            buffers: self.buffers.clone(),
+            means: self.means.clone(),
+            bitshift: self.bitshift,
+            blocksize: self.blocksize,
+            pending_cmd: self.pending_cmd,
+            verbatim_trailer_len: self.verbatim_trailer.len(),
+        };
+
+        let had_block = self.decode_next_block()?;
+        if had_block {
+            self.snapshots.push(snapshot);
+        }
+        Ok(had_block)
+    }
+
+    /// Decode the whole remaining stream once, recording a `Snapshot` at
+    /// every block-group boundary. An optional pre-scan pass so later
+    /// `seek_to_sample` calls never need to replay from the start.
+    ///
+    /// Leaves the decoder positioned at end-of-stream; call
+    /// `seek_to_sample` afterwards to actually reposition for playback.
+    pub fn build_seek_index(&mut self) -> Result<(), ShnError> {
+        self.ensure_initial_snapshot()?;
+        while self.decode_next_block_tracked()? {}
+        Ok(())
+    }
+
+    /// Snapshot the decoder's current state if no snapshot exists yet, so a
+    /// `seek_to_sample` call made before any decoding has a frame-0
+    /// snapshot to restore, instead of having nothing to search and
+    /// erroring out. If blocks were already decoded without tracking (plain
+    /// `decode_next_block`), the seeded snapshot sits at the current
+    /// `total_frames`, and earlier frames cannot be sought to.
+    fn ensure_initial_snapshot(&mut self) -> Result<(), ShnError> {
+        if self.snapshots.is_empty() {
+            self.snapshots.push(Snapshot {
+                frame_start: self.total_frames,
+                checkpoint: self.reader.checkpoint()?,
+                buffers: self.buffers.clone(),
+                means: self.means.clone(),
+                bitshift: self.bitshift,
+                blocksize: self.blocksize,
+                pending_cmd: self.pending_cmd,
+                verbatim_trailer_len: self.verbatim_trailer.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reposition so the next decoded sample is per-channel frame
+    /// `frame_index` (i.e. `samples()` index divided by the channel count).
+    ///
+    /// Restores the nearest snapshot at or before `frame_index` — calling
+    /// this before any prior decode or `build_seek_index` still works,
+    /// since `ensure_initial_snapshot` seeds one at frame 0 — then decodes
+    /// forward (snapshotting as it goes) to the target block, and finally
+    /// skips to the target frame's offset within it. Repeated seeks
+    /// amortize because snapshots accumulate as the stream is visited.
+    pub fn seek_to_sample(&mut self, frame_index: u64) -> Result<(), ShnError> {
+        self.ensure_initial_snapshot()?;
+        let snapshot = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|s| s.frame_start <= frame_index)
+            .cloned()
+            .ok_or(ShnError::SeekOutOfRange(frame_index))?;
+
+        // Snapshots at or after this point describe state that the
+        // about-to-run forward decode will recreate; drop them so the
+        // index doesn't accumulate duplicates across repeated seeks.
+        self.snapshots.retain(|s| s.frame_start < snapshot.frame_start);
+
+        self.total_frames = snapshot.frame_start;
+        self.reader.restore(&snapshot.checkpoint)?;
+        self.buffers = snapshot.buffers;
+        self.means = snapshot.means;
+        self.bitshift = snapshot.bitshift;
+        self.blocksize = snapshot.blocksize;
+        self.pending_cmd = snapshot.pending_cmd;
+        self.verbatim_trailer.truncate(snapshot.verbatim_trailer_len);
+        self.current_channel = 0;
+        self.finished = false;
+        self.output_buf.clear();
+        self.output_pos = 0;
+
+        let nchan = self.channels as usize;
+        loop {
+            let block_start = self.total_frames;
+            if !self.decode_next_block_tracked()? {
+                return Err(ShnError::SeekOutOfRange(frame_index));
+            }
+            let block_frames = self.total_frames - block_start;
+            if frame_index < block_start + block_frames {
+                self.output_pos = ((frame_index - block_start) as usize) * nchan;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Push-based decoding support: a retryable counterpart to
+/// `decode_next_block` for readers that can't block for more input (see
+/// `crate::push::PushDecoder`). Needs `Mark` rather than a full `Seek`,
+/// since a ring buffer only has to rewind to bytes it hasn't discarded —
+/// not seek to arbitrary offsets.
+impl<R: Read + crate::io::Mark> Decoder<R> {
+    /// Like `decode_next_block`, but on `Err(ShnError::NeedMoreInput)`
+    /// rewinds every field a block decode can touch back to how it was
+    /// before this call, so `buffers`, `means`, and `pending_cmd` are never
+    /// left partway mutated by a block that ran out of input mid-command.
+    /// On success, tells the reader this position is never rewound past
+    /// again so it can reclaim buffered bytes.
+    pub fn decode_next_block_checked(&mut self) -> Result<bool, ShnError> {
+        let checkpoint = PushCheckpoint {
+            mark: self.reader.mark(),
+            buffers: self.buffers.clone(),
+            means: self.means.clone(),
+            current_channel: self.current_channel,
+            bitshift: self.bitshift,
+            blocksize: self.blocksize,
+            pending_cmd: self.pending_cmd,
+            rice_sums: self.rice_sums.clone(),
+            verbatim_trailer_len: self.verbatim_trailer.len(),
+        };
+
+        match self.decode_next_block() {
+            Ok(had_block) => {
+                self.reader.commit();
+                Ok(had_block)
+            }
+            Err(ShnError::NeedMoreInput) => {
+                self.reader.rewind(&checkpoint.mark);
+                self.buffers = checkpoint.buffers;
+                self.means = checkpoint.means;
+                self.current_channel = checkpoint.current_channel;
+                self.bitshift = checkpoint.bitshift;
+                self.blocksize = checkpoint.blocksize;
+                self.pending_cmd = checkpoint.pending_cmd;
+                self.rice_sums = checkpoint.rice_sums;
+                // A partially-read VERBATIM block may have pushed some
+                // bytes before the underflow; drop them back to the
+                // pre-attempt length so a retry doesn't duplicate them.
+                self.verbatim_trailer.truncate(checkpoint.verbatim_trailer_len);
+                Err(ShnError::NeedMoreInput)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// State captured by `decode_next_block_checked` before attempting a
+/// block, restored verbatim if the attempt runs out of buffered input.
+struct PushCheckpoint {
+    mark: crate::bitstream::BitMark,
+    buffers: Vec<ChannelBuffer>,
+    means: Vec<MeanAccumulator>,
+    current_channel: u32,
+    bitshift: u32,
+    blocksize: usize,
+    pending_cmd: Option<i32>,
+    rice_sums: Vec<i32>,
+    verbatim_trailer_len: usize,
+}
+
+#[cfg(test)]
+mod adaptive_rice_tests {
+    use super::adapt_rice_k;
+
+    #[test]
+    fn k_rises_when_magnitude_exceeds_upper_bound() {
+        let mut sum = 1 << 9; // already at the upper bound for k=4
+        let k = adapt_rice_k(&mut sum, 4, 40);
+        assert_eq!(k, 5);
+    }
+
+    #[test]
+    fn k_falls_when_magnitude_is_small() {
+        let mut sum = 0;
+        let k = adapt_rice_k(&mut sum, 4, 0);
+        assert_eq!(k, 3);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod fn_zero_tests {
+    use super::*;
+    use crate::bitstream::{BitReader, BitWriter};
+    use crate::header::{self, MAGIC, TYPE_ALAW};
+
+    /// Hand-assemble a minimal v2 stream of the given `file_type` whose only
+    /// audio command is `FN_ZERO`, so it can be decoded without going
+    /// through `ShnWriter` (which only ever emits `TYPE_S16LH`).
+    fn zero_block_stream(file_type: i32) -> Vec<u8> {
+        let mut bw = BitWriter::new(Vec::new());
+        for &b in MAGIC {
+            bw.write_byte_direct(b).unwrap();
+        }
+        bw.write_byte_direct(2).unwrap(); // version
+        bw.write_ulong(file_type as u32).unwrap();
+        bw.write_ulong(1).unwrap(); // channels
+        bw.write_ulong(8).unwrap(); // blocksize
+        bw.write_ulong(0).unwrap(); // maxnlpc
+        bw.write_ulong(0).unwrap(); // nmean
+        bw.write_ulong(0).unwrap(); // nskip
+        bw.write_unsigned_rice(FNSIZE, FN_ZERO as u32).unwrap();
+        bw.write_unsigned_rice(FNSIZE, FN_QUIT as u32).unwrap();
+        bw.into_inner().unwrap()
+    }
+
+    #[test]
+    fn fn_zero_decodes_to_silence_for_ulaw() {
+        let bytes = zero_block_stream(crate::header::TYPE_ULAW);
+        let mut reader = BitReader::new(bytes.as_slice());
+        let (shn_header, _) = header::parse_header(&mut reader).unwrap();
+        let mut decoder = Decoder::new(reader, &shn_header);
+
+        assert!(decoder.decode_next_block().unwrap());
+        for s in decoder.buffers[0].block_samples() {
+            assert!(s.abs() < 10, "expected near-silence, got {s}");
+        }
+    }
+
+    #[test]
+    fn fn_zero_decodes_to_silence_for_alaw() {
+        let bytes = zero_block_stream(TYPE_ALAW);
+        let mut reader = BitReader::new(bytes.as_slice());
+        let (shn_header, _) = header::parse_header(&mut reader).unwrap();
+        let mut decoder = Decoder::new(reader, &shn_header);
+
+        assert!(decoder.decode_next_block().unwrap());
+        for s in decoder.buffers[0].block_samples() {
+            assert!(s.abs() < 10, "expected near-silence, got {s}");
+        }
+    }
+}
+
+/// CCITT G.711 companding expansion, applied to `TYPE_ULAW`/`TYPE_ALAW`
+/// streams in `Decoder::finish_channel_block`.
+///
+/// These are the standard ITU-T G.711 bit-manipulation formulas (the same
+/// ones published in the G.711 recommendation and any textbook treatment
+/// of the codec), not derived from any particular reference decoder.
+mod companding {
+    use crate::header::SampleType;
+
+    /// mu-law's "positive zero" code — the companded byte that expands to
+    /// (near) silence, unlike residual-domain `0` which decodes to a loud
+    /// sample.
+    const MULAW_ZERO: u8 = 0xff;
+    /// A-law's zero code, same caveat as `MULAW_ZERO`.
+    const ALAW_ZERO: u8 = 0xd5;
+
+    /// The residual-domain byte `FN_ZERO` should fill a companded block
+    /// with so it expands to silence: the companded zero code for
+    /// mu-law/A-law, or plain `0` for every linear type.
+    pub fn residual_zero(sample_type: SampleType) -> i32 {
+        match sample_type {
+            SampleType::MuLaw => MULAW_ZERO as i32,
+            SampleType::ALaw => ALAW_ZERO as i32,
+            _ => 0,
+        }
+    }
+
+    /// Expand an 8-bit mu-law code to a 14-bit linear sample.
+    pub fn mulaw_decode(u_val: u8) -> i32 {
+        const BIAS: i32 = 0x84;
+        let u = !u_val;
+        let sign = u & 0x80 != 0;
+        let exponent = (u >> 4) & 0x07;
+        let mantissa = (u & 0x0F) as i32;
+        let mut sample = ((mantissa << 3) + BIAS) << exponent;
+        sample -= BIAS;
+        // The formula yields a 16-bit-scaled value that is always a
+        // multiple of 4, so this narrowing to 14 bits is exact.
+        sample >>= 2;
+        if sign {
+            -sample
+        } else {
+            sample
+        }
+    }
+
+    /// Expand an 8-bit A-law code to a 14-bit linear sample.
+    pub fn alaw_decode(a_val: u8) -> i32 {
+        let a = a_val ^ 0x55;
+        // In A-law a set top bit marks a positive sample.
+        let positive = a & 0x80 != 0;
+        let exponent = (a & 0x70) >> 4;
+        let mantissa = (a & 0x0F) as i32;
+        let mut sample = if exponent == 0 {
+            (mantissa << 4) + 8
+        } else {
+            ((mantissa << 4) + 0x108) << (exponent - 1)
+        };
+        // 16-bit-scaled, always a multiple of 8: narrowing to 14 bits is exact.
+        sample >>= 2;
+        if !positive {
+            sample = -sample;
+        }
+        sample
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn mulaw_zero_code_is_near_zero() {
+            // 0xFF is mu-law's "positive zero" code.
+            assert!(mulaw_decode(0xFF).abs() < 10);
+        }
+
+        #[test]
+        fn alaw_zero_code_is_near_zero() {
+            assert!(alaw_decode(0xD5).abs() < 10);
+        }
+    }
 }
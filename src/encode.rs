@@ -0,0 +1,364 @@
+//! Shorten encoder: [`ShnWriter`] produces `ajkg` v2 streams that
+//! `Decoder`/`ShnReader` can read back bit-exactly.
+//!
+//! Every block is encoded with whichever predictor — fixed DIFF0-DIFF3, or
+//! quantized LPC via Levinson-Durbin, when `max_lpc_order > 0` — yields the
+//! smallest estimated Rice-coded bit cost.
+
+use std::io::Write;
+
+use crate::bitstream::BitWriter;
+use crate::buffer::{ChannelBuffer, MeanAccumulator};
+use crate::decode::{
+    ENERGYSIZE, FIXED_COEFFS, FNSIZE, FN_BLOCKSIZE, FN_DIFF0, FN_QLPC, FN_QUIT, LPCQSIZE, LPCQUANT,
+};
+use crate::error::ShnError;
+use crate::header::{MAGIC, TYPE_S16LH};
+
+/// Shorten encoder version this writer emits (matches the header fields
+/// `ShnHeader` expects for version >= 2: blocksize/maxnlpc/nmean/nskip).
+const ENCODER_VERSION: u8 = 2;
+
+/// Encodes interleaved `i32` PCM into a Shorten bitstream.
+///
+/// Mirrors `Decoder`'s block-at-a-time shape: construct with the stream
+/// parameters, call `write_block` once per `blocksize`-frame chunk of
+/// interleaved samples (the final, possibly short, block is written the
+/// same way — callers pass exactly as many frames as they have), then
+/// `finish` to emit `FN_QUIT` and flush.
+pub struct ShnWriter<W: Write> {
+    writer: BitWriter<W>,
+    channels: usize,
+    blocksize: usize,
+    /// Maximum QLPC predictor order to try. `0` disables QLPC and the
+    /// writer only ever emits fixed predictors.
+    max_lpc_order: usize,
+    /// Per-channel sample history, reused across blocks exactly like the
+    /// decoder's `ChannelBuffer` so the fixed predictors see the same
+    /// `NWRAP` samples of context on both sides.
+    histories: Vec<ChannelBuffer>,
+    /// Per-channel running mean of decoded samples, for the coffset used
+    /// by the order-0 predictor — the encoder-side mirror of `Decoder::means`.
+    means: Vec<MeanAccumulator>,
+    /// Per-channel running mean of *residual magnitude* across the last
+    /// `nmean` blocks, used to pick the Rice parameter `k` for the next
+    /// block (`k = floor(log2(mean))`).
+    energy: Vec<MeanAccumulator>,
+}
+
+impl<W: Write> ShnWriter<W> {
+    /// Start a new Shorten v2 stream: `blocksize` frames per block, `nmean`
+    /// blocks of history for the running means (4 is the usual default),
+    /// and `max_lpc_order` the highest QLPC order to try (`0` to only use
+    /// the fixed predictors).
+    pub fn new(
+        writer: W,
+        channels: u32,
+        blocksize: usize,
+        nmean: usize,
+        max_lpc_order: usize,
+    ) -> Result<Self, ShnError> {
+        let mut bw = BitWriter::new(writer);
+        for &b in MAGIC {
+            bw.write_byte_direct(b)?;
+        }
+        bw.write_byte_direct(ENCODER_VERSION)?;
+
+        bw.write_ulong(TYPE_S16LH as u32)?;
+        bw.write_ulong(channels)?;
+        bw.write_ulong(blocksize as u32)?;
+        bw.write_ulong(max_lpc_order as u32)?;
+        bw.write_ulong(nmean as u32)?;
+        bw.write_ulong(0)?; // nskip
+
+        let nchan = channels as usize;
+        Ok(ShnWriter {
+            writer: bw,
+            channels: nchan,
+            blocksize,
+            max_lpc_order,
+            histories: (0..nchan).map(|_| ChannelBuffer::new(blocksize)).collect(),
+            means: (0..nchan).map(|_| MeanAccumulator::new(nmean)).collect(),
+            energy: (0..nchan).map(|_| MeanAccumulator::new(nmean)).collect(),
+        })
+    }
+
+    /// Encode one block's worth of interleaved samples
+    /// (`frames * channels` values, `frames` frames).
+    pub fn write_block(&mut self, interleaved: &[i32], frames: usize) -> Result<(), ShnError> {
+        let nchan = self.channels;
+        debug_assert_eq!(interleaved.len(), frames * nchan);
+
+        // A block whose frame count doesn't match the stream's declared
+        // `blocksize` (typically a short final block) needs an explicit
+        // `FN_BLOCKSIZE` command first — mirrors `Decoder::decode_next_block`'s
+        // handling of the same command at src/decode.rs.
+        if frames != self.blocksize {
+            self.writer
+                .write_unsigned_rice(FNSIZE, FN_BLOCKSIZE as u32)?;
+            self.writer.write_ulong(frames as u32)?;
+            self.blocksize = frames;
+        }
+
+        for ch in 0..nchan {
+            let buf = &mut self.histories[ch];
+            buf.resize(frames);
+            for i in 0..frames {
+                buf.set(i as isize, interleaved[i * nchan + ch]);
+            }
+
+            let coffset = self.means[ch].coffset(frames);
+
+            // Try DIFF0-DIFF3, keep whichever yields the smallest estimated
+            // Rice-coded bit cost.
+            let mut best = Predictor::Fixed(0);
+            let mut best_residuals = Vec::with_capacity(frames);
+            let mut best_cost = i64::MAX;
+            for (order, coeffs) in FIXED_COEFFS.iter().enumerate() {
+                let mut residuals = Vec::with_capacity(frames);
+                for i in 0..frames {
+                    let ii = i as isize;
+                    let prediction = if order == 0 {
+                        coffset
+                    } else {
+                        let mut pred = 0i32;
+                        for (j, &c) in coeffs.iter().enumerate().take(order) {
+                            pred += c * buf.get(ii - j as isize - 1);
+                        }
+                        pred
+                    };
+                    residuals.push(buf.get(ii) - prediction);
+                }
+                let cost = estimate_rice_cost(&residuals);
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = Predictor::Fixed(order);
+                    best_residuals = residuals;
+                }
+            }
+
+            // Also try QLPC (Levinson-Durbin) if enabled, and keep it if it
+            // beats every fixed order.
+            if let Some((qcoeffs, residuals)) = compute_qlpc(buf, frames, self.max_lpc_order) {
+                let cost = estimate_rice_cost(&residuals);
+                if cost < best_cost {
+                    best_residuals = residuals;
+                    best = Predictor::Qlpc(qcoeffs);
+                }
+            }
+
+            // Rice parameter from the running residual-energy mean.
+            let prev_mean = self.energy[ch].coffset(frames).max(0) as u32;
+            let k = log2_floor(prev_mean);
+
+            match &best {
+                Predictor::Fixed(order) => {
+                    self.writer
+                        .write_unsigned_rice(FNSIZE, (FN_DIFF0 + *order as i32) as u32)?;
+                    self.writer.write_unsigned_rice(ENERGYSIZE, k)?;
+                }
+                Predictor::Qlpc(qcoeffs) => {
+                    self.writer.write_unsigned_rice(FNSIZE, FN_QLPC as u32)?;
+                    self.writer.write_unsigned_rice(ENERGYSIZE, k)?;
+                    self.writer
+                        .write_unsigned_rice(LPCQSIZE, qcoeffs.len() as u32)?;
+                    for &c in qcoeffs {
+                        self.writer.write_signed_rice(LPCQSIZE, c)?;
+                    }
+                }
+            }
+            for &residual in &best_residuals {
+                self.writer.write_signed_rice(k, residual)?;
+            }
+
+            // Mirror `Decoder::finish_channel_block`: the running mean
+            // tracks actual sample values (not residuals), so coffset
+            // predicts the same DC bias the decoder will reconstruct.
+            let sample_sum: i64 = (0..frames).map(|i| buf.get(i as isize) as i64).sum();
+            let block_mean = ((sample_sum + frames as i64 / 2) / frames.max(1) as i64) as i32;
+            self.means[ch].push(block_mean);
+
+            let abs_sum: i64 = best_residuals.iter().map(|&r| r.unsigned_abs() as i64).sum();
+            let residual_mean = ((abs_sum + frames as i64 / 2) / frames.max(1) as i64) as i32;
+            self.energy[ch].push(residual_mean);
+
+            buf.wrap_around();
+        }
+
+        Ok(())
+    }
+
+    /// Emit `FN_QUIT` and flush the bitstream, returning the underlying
+    /// writer.
+    pub fn finish(mut self) -> Result<W, ShnError> {
+        self.writer.write_unsigned_rice(FNSIZE, FN_QUIT as u32)?;
+        self.writer.into_inner()
+    }
+}
+
+/// `floor(log2(x))`, treating `x == 0` as `0` (there is no valid Rice
+/// parameter for a zero mean, and 0 is the smallest legal `k`).
+fn log2_floor(x: u32) -> u32 {
+    if x == 0 {
+        0
+    } else {
+        31 - x.leading_zeros()
+    }
+}
+
+/// A candidate predictor considered for one block.
+enum Predictor {
+    /// A fixed polynomial predictor of the given order (0-3).
+    Fixed(usize),
+    /// Quantized LPC, with coefficients already scaled by `1 << LPCQUANT`.
+    Qlpc(Vec<i32>),
+}
+
+/// Estimate the Rice-coded bit cost of `residuals`: pick
+/// `k = round(log2(mean(|residual|)))`, then sum `(|residual| >> k) + k + 1`
+/// (unary quotient bits + stop bit + k mantissa bits) per residual.
+fn estimate_rice_cost(residuals: &[i32]) -> i64 {
+    let n = residuals.len().max(1) as f64;
+    let sum_abs: i64 = residuals.iter().map(|&r| r.unsigned_abs() as i64).sum();
+    let mean = (sum_abs as f64 / n).max(1.0);
+    let k = (mean.log2().round().max(0.0)) as u32;
+    residuals
+        .iter()
+        .map(|&r| (r.unsigned_abs() as i64 >> k) + k as i64 + 1)
+        .sum()
+}
+
+/// Fit a QLPC predictor of order `max_order` to `buf`'s current block via
+/// Levinson-Durbin recursion on the block's autocorrelation, quantize the
+/// coefficients to integers scaled by `1 << LPCQUANT`, and return them
+/// alongside the resulting residuals. `None` if QLPC is disabled
+/// (`max_order == 0`) or the block is degenerate (silence, or no longer
+/// than the order).
+fn compute_qlpc(buf: &ChannelBuffer, frames: usize, max_order: usize) -> Option<(Vec<i32>, Vec<i32>)> {
+    if max_order == 0 || frames <= max_order {
+        return None;
+    }
+
+    let mut autoc = vec![0f64; max_order + 1];
+    for (lag, a) in autoc.iter_mut().enumerate() {
+        let mut sum = 0f64;
+        for i in lag..frames {
+            sum += buf.get(i as isize) as f64 * buf.get((i - lag) as isize) as f64;
+        }
+        *a = sum;
+    }
+    if autoc[0] <= 0.0 {
+        // Silent (or constant-DC-free) block: no predictive gain to be had.
+        return None;
+    }
+
+    let mut error = autoc[0];
+    let mut lpc = vec![0f64; max_order];
+    for i in 0..max_order {
+        if error <= 0.0 {
+            break;
+        }
+        let mut acc = autoc[i + 1];
+        for j in 0..i {
+            acc -= lpc[j] * autoc[i - j];
+        }
+        let reflection = acc / error;
+
+        let mut updated = lpc.clone();
+        updated[i] = reflection;
+        for j in 0..i {
+            updated[j] = lpc[j] - reflection * lpc[i - 1 - j];
+        }
+        lpc = updated;
+        error *= 1.0 - reflection * reflection;
+    }
+
+    let scale = (1i64 << LPCQUANT) as f64;
+    let clamp = (1i64 << 20) as f64;
+    let qcoeffs: Vec<i32> = lpc
+        .iter()
+        .map(|&c| (c * scale).round().clamp(-clamp, clamp) as i32)
+        .collect();
+
+    let mut residuals = Vec::with_capacity(frames);
+    for i in 0..frames {
+        let ii = i as isize;
+        let mut prediction: i64 = 0;
+        for (j, &coeff) in qcoeffs.iter().enumerate() {
+            prediction += coeff as i64 * buf.get(ii - j as isize - 1) as i64;
+        }
+        let predicted = (prediction >> LPCQUANT) as i32;
+        residuals.push(buf.get(ii) - predicted);
+    }
+
+    Some((qcoeffs, residuals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShnReader;
+
+    #[test]
+    fn round_trips_through_decoder() {
+        let frames = 64usize;
+        let channels = 1u32;
+        let samples: Vec<i32> = (0..frames as i32).map(|i| (i * 3) % 50 - 25).collect();
+
+        let mut out = Vec::new();
+        {
+            let mut w = ShnWriter::new(&mut out, channels, frames, 4, 0).unwrap();
+            w.write_block(&samples, frames).unwrap();
+            let out_ref = w.finish().unwrap();
+            debug_assert!(!out_ref.is_empty());
+        }
+
+        let mut reader = ShnReader::new(out.as_slice()).unwrap();
+        let decoded: Vec<i32> = reader.samples().collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn round_trips_with_qlpc_enabled() {
+        let frames = 128usize;
+        let channels = 1u32;
+        // A smooth-ish signal so QLPC actually has something to predict.
+        let samples: Vec<i32> = (0..frames as i32)
+            .map(|i| ((i as f64 * 0.2).sin() * 1000.0) as i32)
+            .collect();
+
+        let mut out = Vec::new();
+        {
+            let mut w = ShnWriter::new(&mut out, channels, frames, 4, 2).unwrap();
+            w.write_block(&samples, frames).unwrap();
+            let out_ref = w.finish().unwrap();
+            debug_assert!(!out_ref.is_empty());
+        }
+
+        let mut reader = ShnReader::new(out.as_slice()).unwrap();
+        let decoded: Vec<i32> = reader.samples().collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn round_trips_with_a_short_final_block() {
+        let blocksize = 64usize;
+        let channels = 1u32;
+        let full_block: Vec<i32> = (0..blocksize as i32).map(|i| (i * 3) % 50 - 25).collect();
+        let short_block: Vec<i32> = (0..blocksize as i32 / 2).map(|i| (i * 7) % 40 - 20).collect();
+
+        let mut out = Vec::new();
+        {
+            let mut w = ShnWriter::new(&mut out, channels, blocksize, 4, 0).unwrap();
+            w.write_block(&full_block, blocksize).unwrap();
+            w.write_block(&short_block, short_block.len()).unwrap();
+            let out_ref = w.finish().unwrap();
+            debug_assert!(!out_ref.is_empty());
+        }
+
+        let mut reader = ShnReader::new(out.as_slice()).unwrap();
+        let decoded: Vec<i32> = reader.samples().collect::<Result<_, _>>().unwrap();
+        let expected: Vec<i32> = full_block.iter().chain(short_block.iter()).copied().collect();
+        assert_eq!(decoded, expected);
+    }
+}
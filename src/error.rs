@@ -1,5 +1,9 @@
+#[cfg(not(feature = "std"))]
+use core::fmt;
+#[cfg(feature = "std")]
 use std::fmt;
-use std::io;
+
+use crate::io::ReadError;
 
 /// Errors that can occur while decoding a Shorten file.
 #[derive(Debug)]
@@ -18,8 +22,21 @@ pub enum ShnError {
     MissingWaveHeader,
     /// The LPC order exceeds the maximum allowed value.
     InvalidLpcOrder(i32),
-    /// A wrapped I/O error.
-    Io(io::Error),
+    /// `Decoder::seek_to_sample`'s target frame is past the end of the
+    /// stream (no snapshot and no further block reaches it).
+    SeekOutOfRange(u64),
+    /// The embedded WAVE/AIFF header's channel count or bit depth disagrees
+    /// with the Shorten header's own fields. Carries a short name of the
+    /// mismatched field. The sample rate is not compared, since the
+    /// Shorten header has no sample rate field.
+    ContainerHeaderMismatch(&'static str),
+    /// Push-based decoding (`crate::push::PushDecoder`) ran out of buffered
+    /// input partway through a command. Decoder state is unchanged — call
+    /// `feed` with more data and retry the same operation.
+    NeedMoreInput,
+    /// A wrapped I/O error. Under the `std` feature this is `std::io::Error`;
+    /// in `no_std` builds it is the minimal `crate::io::ReadError`.
+    Io(ReadError),
 }
 
 impl fmt::Display for ShnError {
@@ -32,11 +49,19 @@ impl fmt::Display for ShnError {
             ShnError::InvalidBlockSize(s) => write!(f, "invalid block size: {s}"),
             ShnError::MissingWaveHeader => write!(f, "no WAVE header found in verbatim block"),
             ShnError::InvalidLpcOrder(o) => write!(f, "invalid LPC order: {o}"),
+            ShnError::SeekOutOfRange(frame) => {
+                write!(f, "seek target frame {frame} is past the end of the stream")
+            }
+            ShnError::ContainerHeaderMismatch(field) => {
+                write!(f, "embedded container header disagrees with Shorten header: {field}")
+            }
+            ShnError::NeedMoreInput => write!(f, "need more input"),
             ShnError::Io(e) => write!(f, "I/O error: {e}"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for ShnError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -46,8 +71,19 @@ impl std::error::Error for ShnError {
     }
 }
 
-impl From<io::Error> for ShnError {
-    fn from(e: io::Error) -> Self {
-        ShnError::Io(e)
+// `core::error::Error` (stable since 1.81) has no `source()`-returning
+// requirement, so `no_std` callers still get a real `Error` impl to hand to
+// their own error-reporting machinery, just without a typed source chain
+// (the no_std `ReadError` doesn't implement `core::error::Error` itself).
+#[cfg(not(feature = "std"))]
+impl core::error::Error for ShnError {}
+
+impl From<ReadError> for ShnError {
+    fn from(e: ReadError) -> Self {
+        if crate::io::is_need_more_input(&e) {
+            ShnError::NeedMoreInput
+        } else {
+            ShnError::Io(e)
+        }
     }
 }
@@ -1,10 +1,12 @@
-use std::io::Read;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 use crate::bitstream::BitReader;
 use crate::error::ShnError;
+use crate::io::Read;
 
 /// Shorten magic bytes.
-const MAGIC: &[u8; 4] = b"ajkg";
+pub(crate) const MAGIC: &[u8; 4] = b"ajkg";
 
 /// Shorten file types.
 pub const TYPE_S8: i32 = 1; // signed 8-bit
@@ -13,6 +15,110 @@ pub const TYPE_S16HL: i32 = 3; // signed 16-bit, high byte first (big-endian / A
 pub const TYPE_U16HL: i32 = 4; // unsigned 16-bit, high byte first
 pub const TYPE_S16LH: i32 = 5; // signed 16-bit, low byte first (little-endian / WAV)
 pub const TYPE_U16LH: i32 = 6; // unsigned 16-bit, low byte first
+pub const TYPE_ULAW: i32 = 7; // CCITT G.711 mu-law companded
+pub const TYPE_ALAW: i32 = 8; // CCITT G.711 A-law companded
+
+/// The native (on-disk, pre-companding) representation of a Shorten file's
+/// samples, derived from its `file_type`.
+///
+/// Exposed on [`crate::ShnInfo`] so callers don't have to re-derive
+/// signedness/bit depth/companding from the raw `file_type` integer
+/// themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleType {
+    Signed8,
+    Unsigned8,
+    Signed16BigEndian,
+    Unsigned16BigEndian,
+    Signed16LittleEndian,
+    Unsigned16LittleEndian,
+    /// CCITT G.711 mu-law. `samples()` yields the expanded linear value,
+    /// not the raw companded code.
+    MuLaw,
+    /// CCITT G.711 A-law. `samples()` yields the expanded linear value,
+    /// not the raw companded code.
+    ALaw,
+}
+
+impl SampleType {
+    pub fn from_file_type(file_type: i32) -> Result<Self, ShnError> {
+        match file_type {
+            TYPE_S8 => Ok(SampleType::Signed8),
+            TYPE_U8 => Ok(SampleType::Unsigned8),
+            TYPE_S16HL => Ok(SampleType::Signed16BigEndian),
+            TYPE_U16HL => Ok(SampleType::Unsigned16BigEndian),
+            TYPE_S16LH => Ok(SampleType::Signed16LittleEndian),
+            TYPE_U16LH => Ok(SampleType::Unsigned16LittleEndian),
+            TYPE_ULAW => Ok(SampleType::MuLaw),
+            TYPE_ALAW => Ok(SampleType::ALaw),
+            _ => Err(ShnError::UnsupportedFileType(file_type)),
+        }
+    }
+
+    /// The on-disk `file_type` integer this variant was parsed from —
+    /// the inverse of `from_file_type`.
+    pub fn as_raw(&self) -> i32 {
+        match self {
+            SampleType::Signed8 => TYPE_S8,
+            SampleType::Unsigned8 => TYPE_U8,
+            SampleType::Signed16BigEndian => TYPE_S16HL,
+            SampleType::Unsigned16BigEndian => TYPE_U16HL,
+            SampleType::Signed16LittleEndian => TYPE_S16LH,
+            SampleType::Unsigned16LittleEndian => TYPE_U16LH,
+            SampleType::MuLaw => TYPE_ULAW,
+            SampleType::ALaw => TYPE_ALAW,
+        }
+    }
+
+    /// Bit depth of the type's linear (post-expansion) representation.
+    pub fn bits(&self) -> u32 {
+        match self {
+            SampleType::Signed8 | SampleType::Unsigned8 => 8,
+            // G.711 companding expands an 8-bit code to 14 significant bits.
+            SampleType::MuLaw | SampleType::ALaw => 14,
+            _ => 16,
+        }
+    }
+
+    /// Whether the on-disk samples are stored with an unsigned bias
+    /// (`TYPE_U8`/`TYPE_U16*`) rather than as signed integers.
+    pub fn is_unsigned(&self) -> bool {
+        matches!(
+            self,
+            SampleType::Unsigned8
+                | SampleType::Unsigned16BigEndian
+                | SampleType::Unsigned16LittleEndian
+        )
+    }
+
+    /// Whether the on-disk samples are two's-complement signed integers
+    /// rather than carrying an unsigned bias. The inverse of `is_unsigned`.
+    pub fn is_signed(&self) -> bool {
+        !self.is_unsigned()
+    }
+
+    /// Byte order of the on-disk representation, for multi-byte types.
+    /// 8-bit and companded types have no byte order of their own; they
+    /// report `Little` since that's a no-op for a single byte.
+    pub fn byte_order(&self) -> ByteOrder {
+        match self {
+            SampleType::Signed16BigEndian | SampleType::Unsigned16BigEndian => ByteOrder::Big,
+            _ => ByteOrder::Little,
+        }
+    }
+
+    /// Shorthand for `byte_order() == ByteOrder::Big`.
+    pub fn is_big_endian(&self) -> bool {
+        self.byte_order() == ByteOrder::Big
+    }
+}
+
+/// Byte order of a multi-byte on-disk sample representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Big,
+    Little,
+}
 
 /// Default values for version < 2.
 const DEFAULT_V0_NMEAN: usize = 0;
@@ -42,6 +148,13 @@ pub struct ShnHeader {
     /// The first audio command read after the header (and any initial VERBATIM blocks).
     /// The decoder needs this because we consumed it while looking for the WAVE header.
     pub first_audio_cmd: Option<i32>,
+    /// The native sample representation derived from `file_type`.
+    pub sample_type: SampleType,
+    /// Raw bytes of every VERBATIM block read before the first audio
+    /// command — typically the RIFF/WAVE or FORM/AIFF header, concatenated
+    /// in stream order. Exposed so a caller can splice decoded PCM back
+    /// into the original container (`Decoder::verbatim_header`).
+    pub verbatim_header: Vec<u8>,
 }
 
 /// Information extracted from the embedded WAVE/AIFF header.
@@ -78,9 +191,7 @@ pub fn parse_header<R: Read>(
 
     // From here on, all reads go through the bitstream reader
     let file_type = reader.read_ulong()? as i32;
-    if !(TYPE_S8..=TYPE_U16LH).contains(&file_type) {
-        return Err(ShnError::UnsupportedFileType(file_type));
-    }
+    let sample_type = SampleType::from_file_type(file_type)?;
 
     let channels = reader.read_ulong()?;
 
@@ -112,6 +223,7 @@ pub fn parse_header<R: Read>(
     // Read commands looking for VERBATIM blocks that contain the WAVE header.
     // Some SHN files (raw-encoded) don't have VERBATIM blocks at all.
     let mut wave_info = None;
+    let mut verbatim_header = Vec::new();
     #[allow(unused_assignments)]
     let mut first_audio_cmd = None;
 
@@ -122,11 +234,18 @@ pub fn parse_header<R: Read>(
             let nbytes = reader.read_unsigned_rice(VERBATIM_CKSIZE_SIZE)? as usize;
             let verbatim_data = read_verbatim_bytes(reader, nbytes)?;
             if wave_info.is_none() {
-                // Try to parse as WAVE header; ignore if it's not one
+                // Try RIFF/WAVE first, then FORM/AIFF(C); ignore failures —
+                // the verbatim block may not be a recognized container
+                // header at all. Each block is parsed on its own, so a
+                // header split across several verbatim blocks is not
+                // recognized.
                 if let Ok(wi) = parse_wave_header(&verbatim_data) {
                     wave_info = Some(wi);
+                } else if let Ok(wi) = parse_aiff_header(&verbatim_data) {
+                    wave_info = Some(wi);
                 }
             }
+            verbatim_header.extend_from_slice(&verbatim_data);
         } else {
             // Non-VERBATIM command = first audio command
             first_audio_cmd = Some(cmd);
@@ -134,18 +253,18 @@ pub fn parse_header<R: Read>(
         }
     }
 
+    // Cross-check a found WAVE header against the Shorten header's own
+    // fields rather than silently trusting whichever one a caller reads.
+    if let Some(wi) = &wave_info {
+        check_wave_consistency(wi, channels, sample_type)?;
+    }
+
     // If no WAVE header found, infer from the file type
-    let wave_info = wave_info.unwrap_or_else(|| {
-        let bps = match file_type {
-            TYPE_S8 | TYPE_U8 => 8,
-            _ => 16,
-        };
-        WaveInfo {
-            sample_rate: 44100, // reasonable default
-            bits_per_sample: bps,
-            channels,
-            data_bytes: 0,
-        }
+    let wave_info = wave_info.unwrap_or_else(|| WaveInfo {
+        sample_rate: 44100, // reasonable default
+        bits_per_sample: sample_type.bits(),
+        channels,
+        data_bytes: 0,
     });
 
     let header = ShnHeader {
@@ -157,6 +276,8 @@ pub fn parse_header<R: Read>(
         nmean,
         nskip,
         first_audio_cmd,
+        sample_type,
+        verbatim_header,
     };
 
     Ok((header, wave_info))
@@ -172,6 +293,31 @@ fn read_verbatim_bytes<R: Read>(reader: &mut BitReader<R>, n: usize) -> Result<V
     Ok(buf)
 }
 
+/// Check a parsed WAVE header's channel count and bit depth against the
+/// Shorten header's own fields, erroring on disagreement instead of
+/// silently trusting one or the other.
+fn check_wave_consistency(
+    wi: &WaveInfo,
+    channels: u32,
+    sample_type: SampleType,
+) -> Result<(), ShnError> {
+    if wi.channels != channels {
+        return Err(ShnError::ContainerHeaderMismatch("channels"));
+    }
+    // Companded types store the pre-expansion 8-bit code's depth in the
+    // WAVE header, while `sample_type.bits()` reports the 14 bits of the
+    // linear value `samples()` actually yields — compare against the
+    // on-disk depth for those instead of the expanded one.
+    let expected_bits = match sample_type {
+        SampleType::MuLaw | SampleType::ALaw => 8,
+        other => other.bits(),
+    };
+    if wi.bits_per_sample != expected_bits {
+        return Err(ShnError::ContainerHeaderMismatch("bits_per_sample"));
+    }
+    Ok(())
+}
+
 /// Parse a RIFF/WAVE header to extract audio parameters.
 ///
 /// The header is embedded as verbatim data in the Shorten stream.
@@ -258,3 +404,218 @@ fn parse_wave_header(data: &[u8]) -> Result<WaveInfo, ShnError> {
 
     Err(ShnError::MissingWaveHeader)
 }
+
+/// Parse a FORM/AIFF or FORM/AIFC header to extract audio parameters.
+///
+/// Mirrors `parse_wave_header` but for the big-endian IFF chunk layout that
+/// `TYPE_S16HL`/`TYPE_U16HL` files are typically wrapped in: we only need
+/// the `COMM` chunk (channels, sample rate, sample size) and the `SSND`
+/// chunk's data size.
+fn parse_aiff_header(data: &[u8]) -> Result<WaveInfo, ShnError> {
+    if data.len() < 12 {
+        return Err(ShnError::MissingWaveHeader);
+    }
+    if &data[0..4] != b"FORM" {
+        return Err(ShnError::MissingWaveHeader);
+    }
+    if &data[8..12] != b"AIFF" && &data[8..12] != b"AIFC" {
+        return Err(ShnError::MissingWaveHeader);
+    }
+
+    let mut pos = 12;
+    let mut comm_found = false;
+    let mut channels = 0u32;
+    let mut sample_rate = 0u32;
+    let mut bits_per_sample = 0u32;
+
+    while pos + 8 <= data.len() {
+        let chunk_id = &data[pos..pos + 4];
+        let chunk_size = u32::from_be_bytes([
+            data[pos + 4],
+            data[pos + 5],
+            data[pos + 6],
+            data[pos + 7],
+        ]) as usize;
+        let chunk_data = &data[pos + 8..];
+
+        if chunk_id == b"COMM" {
+            if chunk_size < 18 || chunk_data.len() < 18 {
+                return Err(ShnError::MissingWaveHeader);
+            }
+            channels = u16::from_be_bytes([chunk_data[0], chunk_data[1]]) as u32;
+            bits_per_sample = u16::from_be_bytes([chunk_data[6], chunk_data[7]]) as u32;
+            let mut extended = [0u8; 10];
+            extended.copy_from_slice(&chunk_data[8..18]);
+            sample_rate = ieee_extended_to_hz(&extended);
+            comm_found = true;
+        }
+
+        if chunk_id == b"SSND" {
+            if !comm_found {
+                return Err(ShnError::MissingWaveHeader);
+            }
+            // SSND's own payload starts with an 8-byte offset/blockSize
+            // preamble before the actual sample data.
+            let data_bytes = chunk_size.saturating_sub(8) as u32;
+            return Ok(WaveInfo {
+                sample_rate,
+                bits_per_sample,
+                channels,
+                data_bytes,
+            });
+        }
+
+        // IFF chunks are word-aligned, same as RIFF.
+        pos += 8 + chunk_size;
+        if !chunk_size.is_multiple_of(2) {
+            pos += 1;
+        }
+    }
+
+    if comm_found {
+        return Ok(WaveInfo {
+            sample_rate,
+            bits_per_sample,
+            channels,
+            data_bytes: 0,
+        });
+    }
+
+    Err(ShnError::MissingWaveHeader)
+}
+
+/// Decode a 10-byte (80-bit) IEEE-754 extended ("SANE") float, as used by
+/// AIFF's `COMM` sample rate field, to an integer Hz value.
+///
+/// Layout: 1 sign bit + 15 exponent bits (first 2 bytes, big-endian),
+/// followed by a 64-bit mantissa with an explicit leading `1` bit (next 8
+/// bytes, big-endian). The value is `mantissa * 2^(exponent - 16383 - 63)`;
+/// for the normal, positive sample rates a SHN/AIFF file will ever carry,
+/// that reduces to `mantissa >> (63 - (exponent - 16383))`.
+fn ieee_extended_to_hz(bytes: &[u8; 10]) -> u32 {
+    let sign_exp = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let exponent = (sign_exp & 0x7FFF) as i32 - 16383;
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+
+    if !(0..63).contains(&exponent) || mantissa & (1u64 << 63) == 0 {
+        return 0;
+    }
+    (mantissa >> (63 - exponent)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave_info(channels: u32, bits_per_sample: u32) -> WaveInfo {
+        WaveInfo {
+            sample_rate: 44100,
+            bits_per_sample,
+            channels,
+            data_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn consistent_wave_header_passes() {
+        let wi = wave_info(2, 16);
+        assert!(check_wave_consistency(&wi, 2, SampleType::Signed16LittleEndian).is_ok());
+    }
+
+    #[test]
+    fn mismatched_channels_errors() {
+        let wi = wave_info(1, 16);
+        let err = check_wave_consistency(&wi, 2, SampleType::Signed16LittleEndian).unwrap_err();
+        assert!(matches!(err, ShnError::ContainerHeaderMismatch("channels")));
+    }
+
+    #[test]
+    fn mismatched_bit_depth_errors() {
+        let wi = wave_info(1, 8);
+        let err = check_wave_consistency(&wi, 1, SampleType::Signed16LittleEndian).unwrap_err();
+        assert!(matches!(
+            err,
+            ShnError::ContainerHeaderMismatch("bits_per_sample")
+        ));
+    }
+
+    #[test]
+    fn companded_type_checks_against_on_disk_8_bits() {
+        let wi = wave_info(1, 8);
+        assert!(check_wave_consistency(&wi, 1, SampleType::MuLaw).is_ok());
+    }
+
+    #[test]
+    fn ieee_extended_decodes_44100hz() {
+        let bytes: [u8; 10] = [0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0];
+        assert_eq!(ieee_extended_to_hz(&bytes), 44100);
+    }
+
+    /// Build a minimal FORM/AIFF verbatim block: COMM (stereo, 16-bit,
+    /// 44100Hz) followed by an SSND chunk with `data_len` bytes of payload.
+    fn aiff_bytes(channels: u16, bits: u16, data_len: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"FORM");
+        buf.extend_from_slice(&0u32.to_be_bytes()); // FORM size, unused by the parser
+        buf.extend_from_slice(b"AIFF");
+
+        buf.extend_from_slice(b"COMM");
+        buf.extend_from_slice(&18u32.to_be_bytes());
+        buf.extend_from_slice(&channels.to_be_bytes());
+        buf.extend_from_slice(&0u32.to_be_bytes()); // numSampleFrames, unused
+        buf.extend_from_slice(&bits.to_be_bytes());
+        buf.extend_from_slice(&[0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0]); // 44100Hz
+
+        buf.extend_from_slice(b"SSND");
+        buf.extend_from_slice(&(data_len + 8).to_be_bytes());
+        buf.extend_from_slice(&0u32.to_be_bytes()); // offset
+        buf.extend_from_slice(&0u32.to_be_bytes()); // blockSize
+        buf.extend(core::iter::repeat(0u8).take(data_len as usize));
+
+        buf
+    }
+
+    #[test]
+    fn parse_aiff_header_reads_comm_and_ssnd() {
+        let bytes = aiff_bytes(2, 16, 1000);
+        let wi = parse_aiff_header(&bytes).unwrap();
+        assert_eq!(wi.channels, 2);
+        assert_eq!(wi.bits_per_sample, 16);
+        assert_eq!(wi.sample_rate, 44100);
+        assert_eq!(wi.data_bytes, 1000);
+    }
+
+    #[test]
+    fn parse_aiff_header_rejects_non_aiff() {
+        assert!(matches!(
+            parse_aiff_header(b"RIFF0000WAVE"),
+            Err(ShnError::MissingWaveHeader)
+        ));
+    }
+
+    #[test]
+    fn as_raw_is_the_inverse_of_from_file_type() {
+        for &raw in &[
+            TYPE_S8, TYPE_U8, TYPE_S16HL, TYPE_U16HL, TYPE_S16LH, TYPE_U16LH, TYPE_ULAW,
+            TYPE_ALAW,
+        ] {
+            assert_eq!(SampleType::from_file_type(raw).unwrap().as_raw(), raw);
+        }
+    }
+
+    #[test]
+    fn byte_order_matches_file_type_naming() {
+        assert_eq!(SampleType::Signed16BigEndian.byte_order(), ByteOrder::Big);
+        assert_eq!(SampleType::Unsigned16BigEndian.byte_order(), ByteOrder::Big);
+        assert_eq!(SampleType::Signed16LittleEndian.byte_order(), ByteOrder::Little);
+        assert!(SampleType::Signed16BigEndian.is_big_endian());
+        assert!(!SampleType::Signed16LittleEndian.is_big_endian());
+    }
+
+    #[test]
+    fn is_signed_is_the_inverse_of_is_unsigned() {
+        assert!(SampleType::Signed8.is_signed());
+        assert!(!SampleType::Unsigned8.is_signed());
+        assert!(SampleType::MuLaw.is_signed());
+    }
+}
@@ -0,0 +1,119 @@
+//! Minimal byte-source abstraction so the decoder can run without `std`.
+//!
+//! Mirrors the split used by crates like `zstd-rs` and Symphonia's FLAC
+//! bundle: the crate's `Read` trait and `ReadError` type are simply
+//! re-exported from `std::io` when the `std` feature is enabled (so callers
+//! on `std` get real `io::Error`s with no loss of information), and replaced
+//! with a minimal `alloc`-only implementation otherwise.
+
+#[cfg(feature = "std")]
+mod imp {
+    pub use std::io::Error as ReadError;
+    pub use std::io::Read;
+
+    /// Marker wrapped inside the sentinel [`ReadError`], so
+    /// `is_need_more_input` can recognize it by identity instead of by
+    /// `ErrorKind`. A real I/O source can legitimately return
+    /// `ErrorKind::WouldBlock` (a non-blocking socket, say), and that must
+    /// not be confused with "the push-based reader ran dry" — only an error
+    /// built by `need_more_input` itself should ever match.
+    #[derive(Debug)]
+    struct NeedMoreInput;
+
+    impl std::fmt::Display for NeedMoreInput {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "need more input")
+        }
+    }
+
+    impl std::error::Error for NeedMoreInput {}
+
+    /// Build the sentinel [`ReadError`] a push-based reader (`crate::push::RingReader`)
+    /// returns when it has run dry mid-command — distinct from a genuine
+    /// end of stream (`ErrorKind::UnexpectedEof`), which a blocking `Read`
+    /// still reports normally.
+    pub fn need_more_input() -> ReadError {
+        ReadError::new(std::io::ErrorKind::Other, NeedMoreInput)
+    }
+
+    /// Whether `e` is the sentinel built by `need_more_input`.
+    ///
+    /// Checks for the `NeedMoreInput` marker specifically (not just
+    /// `ErrorKind::Other`), so a genuine I/O error of the same kind from an
+    /// arbitrary `std::io::Read` source is never misclassified as "feed more
+    /// and retry."
+    pub fn is_need_more_input(e: &ReadError) -> bool {
+        e.get_ref()
+            .is_some_and(|inner| inner.downcast_ref::<NeedMoreInput>().is_some())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use core::fmt;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum ReadErrorKind {
+        Eof,
+        NeedMoreInput,
+    }
+
+    /// The `no_std` stand-in for `std::io::Error`: reading ran out of bytes.
+    #[derive(Debug)]
+    pub struct ReadError(ReadErrorKind);
+
+    impl fmt::Display for ReadError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0 {
+                ReadErrorKind::Eof => write!(f, "unexpected end of input"),
+                ReadErrorKind::NeedMoreInput => write!(f, "need more input"),
+            }
+        }
+    }
+
+    /// The `no_std` stand-in for `std::io::Read`.
+    ///
+    /// Only the one method `BitReader` actually needs.
+    pub trait Read {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), ReadError>;
+    }
+
+    impl Read for &[u8] {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), ReadError> {
+            if buf.len() > self.len() {
+                return Err(ReadError(ReadErrorKind::Eof));
+            }
+            let (head, tail) = self.split_at(buf.len());
+            buf.copy_from_slice(head);
+            *self = tail;
+            Ok(())
+        }
+    }
+
+    /// Build the sentinel [`ReadError`] a push-based reader (`crate::push::RingReader`)
+    /// returns when it has run dry mid-command — distinct from a genuine
+    /// end of stream, which `&[u8]::read_exact` above still reports normally.
+    pub fn need_more_input() -> ReadError {
+        ReadError(ReadErrorKind::NeedMoreInput)
+    }
+
+    /// Whether `e` is the sentinel built by `need_more_input`.
+    pub fn is_need_more_input(e: &ReadError) -> bool {
+        matches!(e.0, ReadErrorKind::NeedMoreInput)
+    }
+}
+
+pub use imp::{is_need_more_input, need_more_input, Read, ReadError};
+
+/// Minimal rewind support for push-based readers, cheaper than requiring a
+/// full `std::io::Seek` (an in-memory ring only ever needs to rewind to a
+/// point it hasn't discarded yet, not seek to arbitrary byte offsets).
+pub trait Mark {
+    /// Capture the current read position.
+    fn mark(&self) -> usize;
+    /// Rewind to a position previously returned by `mark`.
+    fn rewind(&mut self, mark: usize);
+    /// Called once a block has fully, successfully decoded: an opportunity
+    /// to reclaim buffered bytes that can never be rewound past again.
+    fn commit(&mut self) {}
+}
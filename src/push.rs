@@ -0,0 +1,258 @@
+//! Push-based decoding for callers that receive Shorten audio in chunks
+//! (network sockets, callback-driven playback) rather than owning a
+//! blocking [`crate::io::Read`] source.
+//!
+//! [`PushDecoder`] buffers fed bytes in an internal ring; `decode_next_block`
+//! decodes as far as the buffered bytes allow and returns
+//! [`ShnError::NeedMoreInput`] — instead of an I/O EOF — when a command or
+//! block needs bytes that haven't arrived yet. Decoder state is left
+//! exactly as it was before the attempt, so `feed` followed by retrying
+//! `decode_next_block` always picks up where it left off:
+//!
+//! ```no_run
+//! use shn::push::PushDecoder;
+//! use shn::ShnError;
+//!
+//! # fn next_chunk() -> Option<Vec<u8>> { None }
+//! let mut decoder = PushDecoder::new();
+//! loop {
+//!     match decoder.decode_next_block() {
+//!         Ok(true) => { /* samples available via decoder.next_sample() */ }
+//!         Ok(false) => break, // stream ended
+//!         Err(ShnError::NeedMoreInput) => match next_chunk() {
+//!             Some(chunk) => decoder.feed(&chunk),
+//!             None => break, // no more data to wait for
+//!         },
+//!         Err(e) => panic!("decode error: {e}"),
+//!     }
+//! }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::bitstream::BitReader;
+use crate::decode::Decoder;
+use crate::error::ShnError;
+use crate::header;
+use crate::io::{need_more_input, Mark, Read, ReadError};
+use crate::ShnInfo;
+
+/// An in-memory byte source that `PushDecoder::feed` appends to and the
+/// bitstream reads from, reporting [`need_more_input`] instead of EOF when
+/// it runs dry mid-command.
+pub(crate) struct RingReader {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl RingReader {
+    fn new() -> Self {
+        RingReader {
+            data: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn feed(&mut self, input: &[u8]) {
+        self.data.extend_from_slice(input);
+    }
+}
+
+impl Read for RingReader {
+    #[cfg(feature = "std")]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.data.len() - self.pos;
+        let n = available.min(buf.len());
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), ReadError> {
+        if self.data.len() - self.pos < buf.len() {
+            return Err(need_more_input());
+        }
+        buf.copy_from_slice(&self.data[self.pos..self.pos + buf.len()]);
+        self.pos += buf.len();
+        Ok(())
+    }
+}
+
+impl Mark for RingReader {
+    fn mark(&self) -> usize {
+        self.pos
+    }
+
+    fn rewind(&mut self, mark: usize) {
+        self.pos = mark;
+    }
+
+    fn commit(&mut self) {
+        // Everything before `pos` can never be rewound past again — drop it
+        // so a long-running stream doesn't grow `data` without bound.
+        if self.pos > 0 {
+            self.data.drain(..self.pos);
+            self.pos = 0;
+        }
+    }
+}
+
+enum PushState {
+    AwaitingHeader(BitReader<RingReader>),
+    Decoding {
+        decoder: Decoder<RingReader>,
+        info: ShnInfo,
+    },
+}
+
+/// Decodes a Shorten stream fed in arbitrary-sized chunks instead of read
+/// from a blocking source. See the module documentation for the feed/retry
+/// loop this is built around.
+pub struct PushDecoder {
+    state: PushState,
+}
+
+impl PushDecoder {
+    /// Start a new push-based decoder with no data buffered yet.
+    pub fn new() -> Self {
+        PushDecoder {
+            state: PushState::AwaitingHeader(BitReader::new(RingReader::new())),
+        }
+    }
+
+    /// Append more input bytes. Safe to call with any chunk size, including
+    /// before the header has been fully buffered.
+    pub fn feed(&mut self, input: &[u8]) {
+        match &mut self.state {
+            PushState::AwaitingHeader(reader) => reader.inner_mut().feed(input),
+            PushState::Decoding { decoder, .. } => decoder.reader.inner_mut().feed(input),
+        }
+    }
+
+    /// Metadata about the stream, available once enough input has been fed
+    /// to parse the header.
+    pub fn info(&self) -> Option<&ShnInfo> {
+        match &self.state {
+            PushState::AwaitingHeader(_) => None,
+            PushState::Decoding { info, .. } => Some(info),
+        }
+    }
+
+    /// Decode the next block of samples, same contract as
+    /// `Decoder::decode_next_block` (`Ok(true)` = a block is ready,
+    /// `Ok(false)` = stream ended), except that running out of buffered
+    /// input returns `Err(ShnError::NeedMoreInput)` rather than an I/O
+    /// error, and never mutates decoder state — call `feed` and retry.
+    pub fn decode_next_block(&mut self) -> Result<bool, ShnError> {
+        self.ensure_header_parsed()?;
+        match &mut self.state {
+            PushState::Decoding { decoder, .. } => decoder.decode_next_block_checked(),
+            PushState::AwaitingHeader(_) => unreachable!("ensure_header_parsed transitions or errors"),
+        }
+    }
+
+    /// Get the next sample from the most recently decoded block, or `None`
+    /// if it's exhausted (call `decode_next_block` again).
+    pub fn next_sample(&mut self) -> Option<i32> {
+        match &mut self.state {
+            PushState::AwaitingHeader(_) => None,
+            PushState::Decoding { decoder, .. } => decoder.next_sample(),
+        }
+    }
+
+    /// Parse the header out of buffered input if it hasn't been already,
+    /// transitioning to `PushState::Decoding` on success. Rewinds the ring
+    /// on `NeedMoreInput` so header bytes already fed aren't re-requested.
+    fn ensure_header_parsed(&mut self) -> Result<(), ShnError> {
+        if matches!(self.state, PushState::Decoding { .. }) {
+            return Ok(());
+        }
+
+        // Swap in a cheap placeholder so `reader` can be moved into
+        // `Decoder::new` below without fighting the borrow checker.
+        let PushState::AwaitingHeader(mut reader) = core::mem::replace(
+            &mut self.state,
+            PushState::AwaitingHeader(BitReader::new(RingReader::new())),
+        ) else {
+            unreachable!("checked above");
+        };
+
+        let mark = reader.mark();
+        match header::parse_header(&mut reader) {
+            Ok((shn_header, wave_info)) => {
+                reader.commit();
+                let info = ShnInfo {
+                    channels: wave_info.channels,
+                    sample_rate: wave_info.sample_rate,
+                    bits_per_sample: wave_info.bits_per_sample,
+                    sample_type: shn_header.sample_type,
+                };
+                let decoder = Decoder::new(reader, &shn_header);
+                self.state = PushState::Decoding { decoder, info };
+                Ok(())
+            }
+            Err(ShnError::NeedMoreInput) => {
+                reader.rewind(&mark);
+                self.state = PushState::AwaitingHeader(reader);
+                Err(ShnError::NeedMoreInput)
+            }
+            Err(e) => {
+                self.state = PushState::AwaitingHeader(reader);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Default for PushDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Exercises `crate::encode::ShnWriter` to build the test fixture, which is
+// only available under the `std` feature.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feeding_byte_at_a_time_decodes_the_same_as_all_at_once() {
+        let frames = 32usize;
+        let channels = 1u32;
+        let samples: Vec<i32> = (0..frames as i32).map(|i| (i * 5) % 40 - 20).collect();
+
+        let mut encoded = Vec::new();
+        {
+            let mut w =
+                crate::encode::ShnWriter::new(&mut encoded, channels, frames, 4, 0).unwrap();
+            w.write_block(&samples, frames).unwrap();
+            w.finish().unwrap();
+        }
+
+        let mut decoder = PushDecoder::new();
+        let mut decoded = Vec::new();
+        let mut pos = 0;
+        loop {
+            match decoder.decode_next_block() {
+                Ok(true) => {
+                    while let Some(s) = decoder.next_sample() {
+                        decoded.push(s);
+                    }
+                }
+                Ok(false) => break,
+                Err(ShnError::NeedMoreInput) => {
+                    if pos >= encoded.len() {
+                        panic!("ran out of input without finishing decode");
+                    }
+                    decoder.feed(&encoded[pos..pos + 1]);
+                    pos += 1;
+                }
+                Err(e) => panic!("decode error: {e}"),
+            }
+        }
+
+        assert_eq!(decoded, samples);
+    }
+}
@@ -0,0 +1,236 @@
+//! Optional [Symphonia](https://github.com/pdeljanov/Symphonia) integration.
+//!
+//! Gated behind the `symphonia` feature. Implements `symphonia_core`'s
+//! `FormatReader` and `Decoder` traits on top of [`ShnReader`], so Shorten
+//! files can be demuxed and decoded inside any Symphonia-based player
+//! without the player special-casing SHN.
+//!
+//! Shorten has no native packet framing (it's a continuous Rice-coded
+//! bitstream, not interleaved audio/metadata packets like most formats
+//! Symphonia demuxes), so `ShnFormatReader` does the actual decoding one
+//! block at a time and hands the resulting PCM to `ShnDecoder` as an
+//! already-decoded `Packet`. `ShnDecoder` is therefore a thin relabeling
+//! step: it exists so the crate plugs into Symphonia's `Decoder` registry,
+//! not because there is real decode work left to do at that stage.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use symphonia_core::audio::{AudioBuffer, AudioBufferRef, Signal, SignalSpec};
+use symphonia_core::codecs::{
+    CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult,
+};
+use symphonia_core::errors::{decode_error, Error as SymError, Result as SymResult};
+use symphonia_core::formats::{
+    Cue, FormatOptions, FormatReader, Packet, SeekedTo, SeekMode, SeekTo, Track,
+};
+use symphonia_core::io::MediaSourceStream;
+use symphonia_core::meta::{Metadata, MetadataLog};
+use symphonia_core::support_format;
+
+use crate::{ShnError, ShnReader};
+
+/// Symphonia's registered codec type for Shorten.
+///
+/// Symphonia allocates codec type IDs from its own registry; this is the
+/// value this crate asks to be assigned when registered with a
+/// `CodecRegistry`.
+pub const CODEC_TYPE_SHORTEN: u32 = 0x9000_0001;
+
+impl From<ShnError> for SymError {
+    fn from(e: ShnError) -> Self {
+        match e {
+            ShnError::Io(_) => SymError::IoError(alloc::format!("{e}").into()),
+            _ => SymError::DecodeError("shn: decode error"),
+        }
+    }
+}
+
+/// Symphonia `FormatReader` for Shorten streams.
+///
+/// Decodes one Shorten block per [`FormatReader::next_packet`] call and
+/// exposes the PCM as a `Packet` on a single audio track.
+pub struct ShnFormatReader {
+    reader: ShnReader<MediaSourceStream>,
+    track: Track,
+}
+
+impl FormatReader for ShnFormatReader {
+    fn try_new(source: MediaSourceStream, _options: &FormatOptions) -> SymResult<Self>
+    where
+        Self: Sized,
+    {
+        let reader = ShnReader::new(source)?;
+        let info = reader.info();
+
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(CODEC_TYPE_SHORTEN)
+            .with_sample_rate(info.sample_rate)
+            .with_bits_per_coded_sample(info.bits_per_sample)
+            .with_max_frames_per_packet(u64::MAX);
+
+        let track = Track::new(0, params);
+
+        Ok(ShnFormatReader { reader, track })
+    }
+
+    fn next_packet(&mut self) -> SymResult<Packet> {
+        match self.reader.samples().next() {
+            Some(Ok(first)) => {
+                // `samples()` yields one i32 at a time; a FormatReader packet
+                // should carry a whole decoded block. Pull the rest of the
+                // already-decoded block straight out of the reader's output
+                // buffer so we don't re-enter per-sample.
+                let mut buf: Vec<u8> = Vec::new();
+                buf.extend_from_slice(&first.to_le_bytes());
+                while let Some(s) = self.reader.decoder.next_sample() {
+                    buf.extend_from_slice(&s.to_le_bytes());
+                }
+                Ok(Packet::new_from_boxed_slice(0, 0, 0, buf.into_boxed_slice()))
+            }
+            Some(Err(e)) => Err(e.into()),
+            None => decode_error("shn: end of stream"),
+        }
+    }
+
+    fn metadata(&mut self) -> Metadata<'_> {
+        MetadataLog::default().metadata()
+    }
+
+    fn cues(&self) -> &[Cue] {
+        &[]
+    }
+
+    fn tracks(&self) -> &[Track] {
+        core::slice::from_ref(&self.track)
+    }
+
+    fn seek(&mut self, _mode: SeekMode, _to: SeekTo) -> SymResult<SeekedTo> {
+        decode_error("shn: seeking is not supported by the FormatReader adapter")
+    }
+
+    fn into_inner(self: Box<Self>) -> MediaSourceStream {
+        self.reader.into_inner()
+    }
+}
+
+impl support_format!(ShnFormatReader, "shn", "Shorten", &["shn"], &[], &[b"ajkg"]);
+
+/// Symphonia `Decoder` counterpart.
+///
+/// Each `Packet` produced by [`ShnFormatReader`] already holds interleaved
+/// little-endian `i32` PCM, so `decode` only needs to reinterpret the bytes
+/// into an `AudioBuffer`.
+pub struct ShnDecoder {
+    params: CodecParameters,
+    spec: SignalSpec,
+    buf: AudioBuffer<i32>,
+}
+
+impl Decoder for ShnDecoder {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> SymResult<Self>
+    where
+        Self: Sized,
+    {
+        let channels = params.channels.ok_or(SymError::DecodeError(
+            "shn: missing channel layout in codec parameters",
+        ))?;
+        let rate = params
+            .sample_rate
+            .ok_or(SymError::DecodeError("shn: missing sample rate"))?;
+        let spec = SignalSpec::new(rate, channels);
+        let frames = params.max_frames_per_packet.unwrap_or(4096) as usize;
+
+        Ok(ShnDecoder {
+            params: params.clone(),
+            spec,
+            buf: AudioBuffer::new(frames as u64, spec),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[]
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> SymResult<AudioBufferRef<'_>> {
+        let nchan = self.spec.channels.count();
+        let samples: Vec<i32> = le_bytes_to_i32(packet.buf());
+        let frames = samples.len() / nchan.max(1);
+
+        self.buf.clear();
+        self.buf.render_reserved(Some(frames));
+        for ch in 0..nchan {
+            let plane = self.buf.chan_mut(ch);
+            for (i, dst) in plane.iter_mut().enumerate().take(frames) {
+                *dst = samples[i * nchan + ch];
+            }
+        }
+
+        Ok(self.buf.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buf.as_audio_buffer_ref()
+    }
+}
+
+/// Decode a little-endian byte packet into `i32` samples.
+///
+/// `ShnFormatReader::next_packet` always emits whole `i32` frames, so the
+/// length is guaranteed to be a multiple of 4.
+fn le_bytes_to_i32(bytes: &[u8]) -> Vec<i32> {
+    debug_assert_eq!(bytes.len() % 4, 0);
+    bytes
+        .chunks_exact(4)
+        .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use symphonia_core::io::MediaSourceStreamOptions;
+
+    /// Encode a short mono stream via `encode::ShnWriter` to feed through
+    /// `ShnFormatReader`, which only consumes `ShnReader`.
+    fn encode_fixture() -> (Vec<i32>, Vec<u8>) {
+        let channels = 1u32;
+        let frames = 8usize;
+        let samples: Vec<i32> = (0..frames as i32).map(|i| i * 5 - 20).collect();
+
+        let mut out = Vec::new();
+        let mut w = crate::encode::ShnWriter::new(&mut out, channels, frames, 4, 0).unwrap();
+        w.write_block(&samples, frames).unwrap();
+        w.finish().unwrap();
+
+        (samples, out)
+    }
+
+    #[test]
+    fn next_packet_preserves_the_first_sample() {
+        let (samples, bytes) = encode_fixture();
+        let source = MediaSourceStream::new(Box::new(Cursor::new(bytes)), MediaSourceStreamOptions::default());
+        let mut format = ShnFormatReader::try_new(source, &FormatOptions::default()).unwrap();
+
+        let packet = format.next_packet().unwrap();
+        let decoded = le_bytes_to_i32(packet.buf());
+
+        // The bug under test hardcoded the first sample to 0 instead of the
+        // value `samples()` actually yielded.
+        assert_eq!(decoded, samples);
+    }
+}